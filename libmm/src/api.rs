@@ -1,11 +1,65 @@
+use crate::db::person::IncompleteCredit;
+use crate::error::{ApiError, Error};
+use serde::de::DeserializeOwned;
 use std::fmt::Display;
 
+mod cache;
+mod executor;
 mod tmdb;
 mod tvmaze;
 
-pub use tmdb::TmdbClient;
+pub use cache::ResponseCache;
+pub use executor::RetryPolicy;
+pub use tmdb::{
+    Image, ImageConfiguration, MovieImages, TmdbClient, TmdbEpisode, TmdbSeason, TmdbTvShow,
+};
 pub use tvmaze::TvMazeClient;
 
+/// Identifier of a title in another database
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExternalId {
+    /// IMDb id, e.g. `tt0944947`
+    Imdb(String),
+    /// TheTVDB id
+    TheTvDb(usize),
+}
+
+/// Source of metadata about titles of type `T`, like movies or TV shows.
+///
+/// Ids passed to [`MetadataProvider::detail`] are ids in database of the provider, like `603`
+/// in TMDB or `tt0133093` in IMDb.
+pub trait MetadataProvider<T> {
+    /// Name of provider shown to user
+    fn name(&self) -> &'static str;
+
+    /// Searches titles by name, `year` narrows results down when known
+    fn search(&self, title: &str, year: Option<usize>) -> Result<Vec<T>, Error>;
+
+    /// Id of title in database of the provider
+    fn id(&self, title: &T) -> String;
+
+    /// Returns `None` when there is no title with given id
+    fn detail(&self, id: &str) -> Result<Option<T>, Error>;
+
+    /// Like [`MetadataProvider::detail`], but ignores and replaces cached data
+    fn refresh(&self, id: &str) -> Result<Option<T>, Error> {
+        self.detail(id)
+    }
+
+    /// Finds title by its id in another database
+    fn find_by_external_id(&self, external_id: &ExternalId) -> Result<Option<T>, Error>;
+
+    /// Cast and crew of title, `None` when provider does not know them
+    fn credits(&self, _id: &str) -> Result<Option<Vec<IncompleteCredit>>, Error> {
+        Ok(None)
+    }
+}
+
+/// Deserializes body of response
+fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T, Error> {
+    serde_json::from_str(body).map_err(|_| ApiError::InvalidFormat.into())
+}
+
 struct QueryBuilder<'a> {
     keys: Vec<String>,
     values: Vec<Box<dyn Display + 'a>>,
@@ -36,7 +90,7 @@ impl<'a> QueryBuilder<'a> {
                 .keys
                 .into_iter()
                 .zip(self.values)
-                .map(|(k, v)| format!("{k}={v}"))
+                .map(|(k, v)| format!("{}={}", encode(&k), encode(&v.to_string())))
                 .collect::<Vec<_>>()
                 .join("&");
             query.push_str(&pairs);
@@ -46,6 +100,22 @@ impl<'a> QueryBuilder<'a> {
     }
 }
 
+/// Percent-encodes everything except unreserved characters of RFC 3986
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +129,40 @@ mod tests {
 
         assert_eq!(String::from("?test=1&test2=aaaaa"), query);
     }
+
+    #[test]
+    fn it_encodes_reserved_characters() {
+        let query = QueryBuilder::new()
+            .add("query", "Tom & Jerry")
+            .add("a=b", "what?#1/2+3%")
+            .build();
+
+        assert_eq!(
+            "?query=Tom%20%26%20Jerry&a%3Db=what%3F%231%2F2%2B3%25",
+            query
+        );
+    }
+
+    #[test]
+    fn it_encodes_unicode() {
+        let query = QueryBuilder::new()
+            .add("query", "Amélie")
+            .add("q", "千と千尋の神隠し")
+            .build();
+
+        assert_eq!(
+            "?query=Am%C3%A9lie&q=%E5%8D%83%E3%81%A8%E5%8D%83%E5%B0%8B%E3%81%AE%E7%A5%9E%E9%9A%A0%E3%81%97",
+            query
+        );
+    }
+
+    #[test]
+    fn it_keeps_empty_values() {
+        let query = QueryBuilder::new()
+            .add("query", "")
+            .add("unreserved", "a-b.c_d~e")
+            .build();
+
+        assert_eq!("?query=&unreserved=a-b.c_d~e", query);
+    }
 }
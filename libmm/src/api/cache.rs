@@ -0,0 +1,164 @@
+use crate::error::{ApiError, Error};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Stores bodies of successful API responses in SQLite database
+pub struct ResponseCache {
+    conn: Connection,
+    offline: bool,
+}
+
+impl ResponseCache {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS `response` (
+                `key` TEXT PRIMARY KEY,
+                `body` TEXT NOT NULL,
+                `fetched_at` INTEGER NOT NULL
+            );",
+        )?;
+
+        Ok(Self {
+            conn,
+            offline: false,
+        })
+    }
+
+    /// In offline mode no requests are sent and cached responses are used regardless of age
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns cached body younger than `ttl`.
+    ///
+    /// ## Remarks
+    /// Fails with [`ApiError::Offline`] when nothing is cached in offline mode.
+    pub(crate) fn get(&self, key: &str, ttl: Duration) -> Result<Option<String>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT `body`, `fetched_at` FROM `response` WHERE `key` = ?")?;
+
+        let cached = stmt
+            .query_row([key], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))
+            .optional()?;
+
+        match cached {
+            Some((body, _)) if self.offline => Ok(Some(body)),
+            Some((body, fetched_at)) if now().saturating_sub(fetched_at) < ttl.as_secs() => {
+                Ok(Some(body))
+            }
+            _ if self.offline => Err(ApiError::Offline.into()),
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) fn insert(&self, key: &str, body: &str) -> Result<(), Error> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO `response` (`key`, `body`, `fetched_at`) VALUES (?, ?, ?)",
+            params![key, body, now()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Removes responses with key `prefix` and its subpaths, e.g. `tmdb:/movie/603/credits`
+    pub(crate) fn invalidate(&self, prefix: &str) -> Result<(), Error> {
+        self.conn.execute(
+            "DELETE FROM `response` WHERE `key` = ?1 OR `key` LIKE ?1 || '/%' OR `key` LIKE ?1 || '?%'",
+            [prefix],
+        )?;
+
+        Ok(())
+    }
+
+    /// Removes all cached responses
+    pub fn clear(&self) -> Result<(), Error> {
+        self.conn.execute("DELETE FROM `response`", [])?;
+
+        Ok(())
+    }
+}
+
+/// Unix time in seconds
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn it_caches_responses() {
+        let cache = ResponseCache::open_in_memory().unwrap();
+        cache.insert("tmdb:/movie/603", "{}").unwrap();
+
+        assert_eq!(
+            cache.get("tmdb:/movie/603", DAY).unwrap(),
+            Some("{}".into())
+        );
+        assert_eq!(cache.get("tmdb:/movie/604", DAY).unwrap(), None);
+    }
+
+    #[test]
+    fn it_expires_responses() {
+        let cache = ResponseCache::open_in_memory().unwrap();
+        cache
+            .conn
+            .execute(
+                "INSERT INTO `response` VALUES ('tmdb:/movie/603', '{}', ?)",
+                [now() - 2 * DAY.as_secs()],
+            )
+            .unwrap();
+
+        assert_eq!(cache.get("tmdb:/movie/603", DAY).unwrap(), None);
+        assert!(cache.get("tmdb:/movie/603", 7 * DAY).unwrap().is_some());
+
+        // stale responses are better than nothing when offline
+        let cache = cache.with_offline(true);
+        assert!(cache.get("tmdb:/movie/603", DAY).unwrap().is_some());
+        assert!(matches!(
+            cache.get("tmdb:/movie/604", DAY),
+            Err(Error::Api(ApiError::Offline))
+        ));
+    }
+
+    #[test]
+    fn it_invalidates_responses() {
+        let cache = ResponseCache::open_in_memory().unwrap();
+        for key in [
+            "tmdb:/movie/603",
+            "tmdb:/movie/603/credits",
+            "tmdb:/movie/603?language=en",
+            "tmdb:/movie/6030",
+        ] {
+            cache.insert(key, "{}").unwrap();
+        }
+
+        cache.invalidate("tmdb:/movie/603").unwrap();
+
+        assert_eq!(cache.get("tmdb:/movie/603", DAY).unwrap(), None);
+        assert_eq!(cache.get("tmdb:/movie/603/credits", DAY).unwrap(), None);
+        assert_eq!(cache.get("tmdb:/movie/603?language=en", DAY).unwrap(), None);
+        assert!(cache.get("tmdb:/movie/6030", DAY).unwrap().is_some());
+    }
+}
@@ -0,0 +1,208 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use ureq::{Agent, ErrorKind, Response};
+
+/// Maximal number of requests sent in given period
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RateLimit {
+    pub requests: usize,
+    pub period: Duration,
+}
+
+/// How many times and how long to wait before failed request is sent again
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Delay before first retry, doubled with each next one
+    pub base_delay: Duration,
+    /// Longest delay between retries, longer `Retry-After` is not waited for
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Sends requests shared by API clients, keeps to rate limit and retries transient failures
+pub(crate) struct RequestExecutor {
+    agent: Agent,
+    rate_limit: RateLimit,
+    retry: RetryPolicy,
+    sent: RefCell<VecDeque<Instant>>,
+}
+
+impl RequestExecutor {
+    pub fn new(rate_limit: RateLimit) -> Self {
+        Self {
+            agent: Agent::new(),
+            rate_limit,
+            retry: RetryPolicy::default(),
+            sent: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Returns last error when request does not succeed even after retries
+    pub fn get(&self, url: &str) -> Result<Response, Box<ureq::Error>> {
+        let mut attempt = 0;
+
+        loop {
+            self.wait_for_rate_limit();
+
+            let res = self.agent.get(url).call();
+
+            let delay = match &res {
+                Err(ureq::Error::Status(status, res)) if is_transient(*status) => {
+                    retry_after(res).unwrap_or_else(|| self.backoff(attempt))
+                }
+                Err(ureq::Error::Transport(t))
+                    if matches!(t.kind(), ErrorKind::ConnectionFailed | ErrorKind::Io) =>
+                {
+                    self.backoff(attempt)
+                }
+                _ => return res.map_err(Box::new),
+            };
+
+            if attempt >= self.retry.max_retries || delay > self.retry.max_delay {
+                return res.map_err(Box::new);
+            }
+
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Blocks until another request fits into rate limit
+    fn wait_for_rate_limit(&self) {
+        let mut sent = self.sent.borrow_mut();
+        let now = Instant::now();
+
+        while sent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= self.rate_limit.period)
+        {
+            sent.pop_front();
+        }
+
+        if sent.len() >= self.rate_limit.requests {
+            if let Some(oldest) = sent.pop_front() {
+                thread::sleep(self.rate_limit.period.saturating_sub(now - oldest));
+            }
+        }
+
+        sent.push_back(Instant::now());
+    }
+
+    /// Exponential backoff with jitter, so that clients do not retry all at once
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .retry
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.retry.max_delay);
+
+        delay / 2 + jitter(delay / 2)
+    }
+}
+
+fn is_transient(status: u16) -> bool {
+    matches!(status, 429 | 500 | 502 | 503 | 504)
+}
+
+/// Only delay in seconds is supported, HTTP dates are ignored
+fn retry_after(res: &Response) -> Option<Duration> {
+    res.header("Retry-After")?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Counted in every jitter, so that it differs even when coarse clock did not move
+static JITTER_CALLS: AtomicU64 = AtomicU64::new(0);
+
+/// Pseudo-random duration up to `max`, precise randomness is not needed here
+fn jitter(max: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let calls = JITTER_CALLS.fetch_add(1, Ordering::Relaxed);
+    let random = splitmix64(nanos ^ (u64::from(std::process::id()) << 32) ^ splitmix64(calls));
+
+    // 53 bits fit into mantissa of `f64`
+    max.mul_f64((random >> 11) as f64 / (1u64 << 53) as f64)
+}
+
+/// Scrambles bits of `seed`, finalizer of SplitMix64 generator
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_backs_off_exponentially() {
+        let mut executor = RequestExecutor::new(RateLimit {
+            requests: 1,
+            period: Duration::from_secs(1),
+        });
+        executor.set_retry_policy(RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        });
+
+        for (attempt, max) in [(0, 100), (1, 200), (2, 300), (5, 300)] {
+            let delay = executor.backoff(attempt);
+            let max = Duration::from_millis(max);
+
+            assert!(delay >= max / 2 && delay <= max, "{attempt}: {delay:?}");
+        }
+    }
+
+    #[test]
+    fn it_varies_jitter() {
+        let max = Duration::from_secs(1);
+        let mut jitters = (0..10).map(|_| jitter(max)).collect::<Vec<_>>();
+
+        assert!(jitters.iter().all(|j| *j <= max));
+
+        jitters.sort();
+        jitters.dedup();
+        assert_eq!(jitters.len(), 10);
+    }
+
+    #[test]
+    fn it_keeps_to_rate_limit() {
+        let executor = RequestExecutor::new(RateLimit {
+            requests: 2,
+            period: Duration::from_millis(50),
+        });
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            executor.wait_for_rate_limit();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -1,49 +1,86 @@
-use crate::api::tmdb::endpoint::TmdbEndpoint;
-use crate::api::tmdb::response::{ErrorInfo, MovieDetail, SearchMovieResponse};
+use crate::api::executor::{RateLimit, RequestExecutor};
+use crate::api::tmdb::endpoint::{movie_path, tv_path, TmdbEndpoint, DEFAULT_BASE_URL};
+use crate::api::tmdb::images::ConfigurationResponse;
+use crate::api::tmdb::response::{
+    CreditsResponse, ErrorInfo, FindResponse, MovieDetail, SearchMovieResponse, SearchTvResponse,
+};
+use crate::api::tmdb::tv::ExternalIds;
+use crate::api::{parse_json, ExternalId, MetadataProvider, ResponseCache, RetryPolicy};
 use crate::error::{ApiError, Error};
 
 use crate::db::movie::IncompleteMovie;
-use ureq::Agent;
+use crate::db::person::IncompleteCredit;
+use serde::de::DeserializeOwned;
+use std::io::Read;
+use std::time::Duration;
 
 mod endpoint;
+mod images;
 mod response;
+mod tv;
+
+pub use images::{Image, ImageConfiguration, MovieImages};
+pub use tv::{TmdbEpisode, TmdbSeason, TmdbTvShow};
+
+/// TMDB allows around 50 requests per second, stay well below it
+const RATE_LIMIT: RateLimit = RateLimit {
+    requests: 40,
+    period: Duration::from_secs(1),
+};
 
 /// Client for The Movie Database (TMDB) API
 pub struct TmdbClient {
     api_key: String,
-    agent: Agent,
+    base_url: String,
+    executor: RequestExecutor,
+    cache: Option<ResponseCache>,
 }
 
 impl TmdbClient {
     pub fn new(api_key: String) -> Self {
-        let agent = ureq::agent();
-        Self { api_key, agent }
+        Self {
+            api_key,
+            base_url: DEFAULT_BASE_URL.into(),
+            executor: RequestExecutor::new(RATE_LIMIT),
+            cache: None,
+        }
+    }
+
+    /// Sends requests to another server, like a local mock of the API
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+
+    /// Changes how failed requests are retried
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.executor.set_retry_policy(retry);
+        self
+    }
+
+    /// Reuses responses stored in `cache` and stores new ones there
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     pub fn get_movie_detail(&self, movie_id: usize) -> Result<Option<IncompleteMovie>, Error> {
-        let url = TmdbEndpoint::GetMovieDetail { movie_id }.url(&self.api_key);
+        let endpoint = TmdbEndpoint::GetMovieDetail { movie_id };
 
-        let res = self.agent.get(&url).call();
+        Ok(self.get::<MovieDetail>(&endpoint)?.map(|md| md.into()))
+    }
 
-        match res {
-            Ok(res) => {
-                let res = res.into_json::<MovieDetail>();
-                match res {
-                    Ok(md) => Ok(Some(md.into())),
-                    Err(_e) => Err(ApiError::InvalidFormat.into()),
-                }
+    /// Removes all cached responses about the movie and fetches its detail again
+    pub fn refresh_movie_detail(&self, movie_id: usize) -> Result<Option<IncompleteMovie>, Error> {
+        if let Some(cache) = &self.cache {
+            if cache.is_offline() {
+                return Err(ApiError::Offline.into());
             }
-            Err(ureq::Error::Status(401, _)) => Err(ApiError::ApiKey.into()),
-            Err(ureq::Error::Status(404, _)) => Ok(None),
-            Err(ureq::Error::Status(_status, res)) => {
-                let res = res.into_json::<ErrorInfo>();
-                match res {
-                    Ok(e) => Err(ApiError::Unknown(e.status_message).into()),
-                    Err(_e) => Err(ApiError::InvalidFormat.into()),
-                }
-            }
-            Err(ureq::Error::Transport(t)) => Err(ApiError::Transport(t).into()),
+
+            cache.invalidate(&format!("tmdb:{}", movie_path(movie_id)))?;
         }
+
+        self.get_movie_detail(movie_id)
     }
 
     /// Returns list of movies.
@@ -55,24 +92,184 @@ impl TmdbClient {
         title: impl AsRef<str>,
         year: Option<usize>,
     ) -> Result<Vec<IncompleteMovie>, Error> {
-        let url = TmdbEndpoint::SearchMovies {
+        let endpoint = TmdbEndpoint::SearchMovies {
+            query: title.as_ref(),
+            year,
+        };
+
+        let response = self.get::<SearchMovieResponse>(&endpoint)?;
+
+        Ok(response
+            .unwrap_or_default()
+            .results
+            .into_iter()
+            .map(|m| m.into())
+            .collect())
+    }
+
+    /// Finds movie by its id in another database.
+    ///
+    /// ## Remarks
+    /// Movie is without runtime length.
+    pub fn find_movie(&self, external_id: &ExternalId) -> Result<Option<IncompleteMovie>, Error> {
+        let endpoint = TmdbEndpoint::FindByExternalId { external_id };
+
+        let response = self.get::<FindResponse>(&endpoint)?;
+
+        Ok(response
+            .unwrap_or_default()
+            .movie_results
+            .into_iter()
+            .next()
+            .map(|m| m.into()))
+    }
+
+    /// Returns list of TV shows, without seasons
+    pub fn search_tvshows_by_title(
+        &self,
+        title: impl AsRef<str>,
+        year: Option<usize>,
+    ) -> Result<Vec<TmdbTvShow>, Error> {
+        let endpoint = TmdbEndpoint::SearchTvShows {
             query: title.as_ref(),
             year,
+        };
+
+        Ok(self
+            .get::<SearchTvResponse>(&endpoint)?
+            .unwrap_or_default()
+            .results)
+    }
+
+    /// Returns TV show with list of its seasons, seasons are without episodes
+    pub fn get_tvshow_detail(&self, tvshow_id: usize) -> Result<Option<TmdbTvShow>, Error> {
+        self.get(&TmdbEndpoint::GetTvShowDetail { tvshow_id })
+    }
+
+    /// Removes all cached responses about the TV show, including seasons, and fetches it again
+    pub fn refresh_tvshow_detail(&self, tvshow_id: usize) -> Result<Option<TmdbTvShow>, Error> {
+        if let Some(cache) = &self.cache {
+            if cache.is_offline() {
+                return Err(ApiError::Offline.into());
+            }
+
+            cache.invalidate(&format!("tmdb:{}", tv_path(tvshow_id)))?;
         }
-        .url(&self.api_key);
 
-        let res = self.agent.get(&url).call();
+        self.get_tvshow_detail(tvshow_id)
+    }
+
+    /// Returns season with its episodes
+    pub fn get_tv_season(
+        &self,
+        tvshow_id: usize,
+        season_number: u32,
+    ) -> Result<Option<TmdbSeason>, Error> {
+        self.get(&TmdbEndpoint::GetTvSeason {
+            tvshow_id,
+            season_number,
+        })
+    }
+
+    /// Returns ids of TV show in other databases, used to find it in TVMaze
+    pub fn get_tvshow_external_ids(&self, tvshow_id: usize) -> Result<Vec<ExternalId>, Error> {
+        let ids = self.get::<ExternalIds>(&TmdbEndpoint::GetTvExternalIds { tvshow_id })?;
+
+        Ok(ids.map(|ids| ids.into()).unwrap_or_default())
+    }
+
+    /// Finds TV show by its id in another database, without seasons
+    pub fn find_tvshow(&self, external_id: &ExternalId) -> Result<Option<TmdbTvShow>, Error> {
+        let endpoint = TmdbEndpoint::FindByExternalId { external_id };
+
+        let response = self.get::<FindResponse>(&endpoint)?;
+
+        Ok(response.unwrap_or_default().tv_results.into_iter().next())
+    }
+
+    /// Returns base URL and sizes of images, needed to download them
+    pub fn get_image_configuration(&self) -> Result<ImageConfiguration, Error> {
+        self.get::<ConfigurationResponse>(&TmdbEndpoint::GetConfiguration)?
+            .map(|c| c.images)
+            .ok_or_else(|| ApiError::InvalidFormat.into())
+    }
+
+    /// Returns cast in billing order followed by crew
+    pub fn get_movie_credits(
+        &self,
+        movie_id: usize,
+    ) -> Result<Option<Vec<IncompleteCredit>>, Error> {
+        let endpoint = TmdbEndpoint::GetMovieCredits { movie_id };
 
-        match res {
+        Ok(self.get::<CreditsResponse>(&endpoint)?.map(|c| c.into()))
+    }
+
+    /// Returns posters, backdrops and logos of movie
+    pub fn get_movie_images(&self, movie_id: usize) -> Result<Option<MovieImages>, Error> {
+        self.get(&TmdbEndpoint::GetMovieImages { movie_id })
+    }
+
+    /// Downloads image in given size, like `w500` or `original`.
+    ///
+    /// ## Remarks
+    /// Images are not cached, in offline mode nothing is downloaded.
+    pub fn download_image(
+        &self,
+        config: &ImageConfiguration,
+        image: &Image,
+        size: &str,
+    ) -> Result<Vec<u8>, Error> {
+        if self.cache.as_ref().is_some_and(|c| c.is_offline()) {
+            return Err(ApiError::Offline.into());
+        }
+
+        let res = self.executor.get(&config.url(image, size));
+
+        match res.map_err(|e| *e) {
             Ok(res) => {
-                let res = res.into_json::<SearchMovieResponse>();
-                match res {
-                    Ok(response) => Ok(response.results.into_iter().map(|m| m.into()).collect()),
-                    Err(_e) => Err(ApiError::InvalidFormat.into()),
+                let mut bytes = Vec::new();
+                res.into_reader()
+                    .read_to_end(&mut bytes)
+                    .map_err(|_| ApiError::InvalidFormat)?;
+
+                Ok(bytes)
+            }
+            Err(ureq::Error::Status(429, _)) => Err(ApiError::RateLimited.into()),
+            Err(ureq::Error::Status(status, _)) => {
+                Err(ApiError::Unknown(format!("Image download failed with status {status}")).into())
+            }
+            Err(ureq::Error::Transport(t)) => Err(ApiError::from(t).into()),
+        }
+    }
+
+    /// Returns `None` when the resource does not exist, only successful responses are cached
+    fn get<T: DeserializeOwned>(&self, endpoint: &TmdbEndpoint) -> Result<Option<T>, Error> {
+        let key = endpoint.cache_key();
+
+        if let Some(cache) = &self.cache {
+            if let Some(body) = cache.get(&key, endpoint.ttl())? {
+                return parse_json(&body).map(Some);
+            }
+        }
+
+        let res = self
+            .executor
+            .get(&endpoint.url(&self.base_url, &self.api_key));
+
+        match res.map_err(|e| *e) {
+            Ok(res) => {
+                let body = res.into_string().map_err(|_| ApiError::InvalidFormat)?;
+                let value = parse_json(&body)?;
+
+                if let Some(cache) = &self.cache {
+                    cache.insert(&key, &body)?;
                 }
+
+                Ok(Some(value))
             }
             Err(ureq::Error::Status(401, _)) => Err(ApiError::ApiKey.into()),
-            Err(ureq::Error::Status(404, _)) => Ok(Vec::new()),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(ureq::Error::Status(429, _)) => Err(ApiError::RateLimited.into()),
             Err(ureq::Error::Status(_status, res)) => {
                 let res = res.into_json::<ErrorInfo>();
                 match res {
@@ -80,7 +277,81 @@ impl TmdbClient {
                     Err(_e) => Err(ApiError::InvalidFormat.into()),
                 }
             }
-            Err(ureq::Error::Transport(t)) => Err(ApiError::Transport(t).into()),
+            Err(ureq::Error::Transport(t)) => Err(ApiError::from(t).into()),
         }
     }
 }
+
+impl MetadataProvider<IncompleteMovie> for TmdbClient {
+    fn name(&self) -> &'static str {
+        "TMDB"
+    }
+
+    fn search(&self, title: &str, year: Option<usize>) -> Result<Vec<IncompleteMovie>, Error> {
+        self.search_movies_by_title(title, year)
+    }
+
+    fn id(&self, title: &IncompleteMovie) -> String {
+        title.provider_id.clone()
+    }
+
+    fn detail(&self, id: &str) -> Result<Option<IncompleteMovie>, Error> {
+        match id.parse() {
+            Ok(id) => self.get_movie_detail(id),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn refresh(&self, id: &str) -> Result<Option<IncompleteMovie>, Error> {
+        match id.parse() {
+            Ok(id) => self.refresh_movie_detail(id),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn find_by_external_id(
+        &self,
+        external_id: &ExternalId,
+    ) -> Result<Option<IncompleteMovie>, Error> {
+        self.find_movie(external_id)
+    }
+
+    fn credits(&self, id: &str) -> Result<Option<Vec<IncompleteCredit>>, Error> {
+        match id.parse() {
+            Ok(id) => self.get_movie_credits(id),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl MetadataProvider<TmdbTvShow> for TmdbClient {
+    fn name(&self) -> &'static str {
+        "TMDB"
+    }
+
+    fn search(&self, title: &str, year: Option<usize>) -> Result<Vec<TmdbTvShow>, Error> {
+        self.search_tvshows_by_title(title, year)
+    }
+
+    fn id(&self, title: &TmdbTvShow) -> String {
+        title.tmdb_id.to_string()
+    }
+
+    fn detail(&self, id: &str) -> Result<Option<TmdbTvShow>, Error> {
+        match id.parse() {
+            Ok(id) => self.get_tvshow_detail(id),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn refresh(&self, id: &str) -> Result<Option<TmdbTvShow>, Error> {
+        match id.parse() {
+            Ok(id) => self.refresh_tvshow_detail(id),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn find_by_external_id(&self, external_id: &ExternalId) -> Result<Option<TmdbTvShow>, Error> {
+        self.find_tvshow(external_id)
+    }
+}
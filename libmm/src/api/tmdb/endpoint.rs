@@ -1,36 +1,140 @@
-use crate::api::QueryBuilder;
-use std::fmt::Display;
+use crate::api::{ExternalId, QueryBuilder};
+use std::time::Duration;
 
-const BASE_URL: &str = "https://api.themoviedb.org/3";
+/// Used when client is not given another base URL
+pub(super) const DEFAULT_BASE_URL: &str = "https://api.themoviedb.org/3";
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
 
 pub enum TmdbEndpoint<'a> {
-    GetMovieDetail { movie_id: usize },
-    SearchMovies { query: &'a str, year: Option<usize> },
+    GetMovieDetail {
+        movie_id: usize,
+    },
+    SearchMovies {
+        query: &'a str,
+        year: Option<usize>,
+    },
+    FindByExternalId {
+        external_id: &'a ExternalId,
+    },
+    SearchTvShows {
+        query: &'a str,
+        year: Option<usize>,
+    },
+    GetTvShowDetail {
+        tvshow_id: usize,
+    },
+    GetTvSeason {
+        tvshow_id: usize,
+        season_number: u32,
+    },
+    GetTvExternalIds {
+        tvshow_id: usize,
+    },
+    GetConfiguration,
+    GetMovieImages {
+        movie_id: usize,
+    },
+    GetMovieCredits {
+        movie_id: usize,
+    },
 }
 
 impl<'a> TmdbEndpoint<'a> {
-    pub fn url(&'a self, api_key: &'a str) -> String {
+    pub fn url(&'a self, base_url: &str, api_key: &'a str) -> String {
+        let (path, query) = self.parts();
+
+        format!("{base_url}{path}{}", query.add("api_key", api_key).build())
+    }
+
+    /// Identifies response in cache, does not contain API key
+    pub fn cache_key(&self) -> String {
+        let (path, query) = self.parts();
+
+        format!("tmdb:{path}{}", query.build())
+    }
+
+    /// How long is cached response considered fresh
+    pub fn ttl(&self) -> Duration {
         match self {
-            Self::GetMovieDetail { movie_id } => build_url(
-                format!("/movie/{movie_id}"),
-                QueryBuilder::new().add("api_key", api_key).build(),
-            ),
+            Self::GetMovieDetail { .. } => 7 * DAY,
+            Self::SearchMovies { .. } => DAY,
+            Self::FindByExternalId { .. } => 30 * DAY,
+            Self::SearchTvShows { .. } => DAY,
+            // running shows get new episodes
+            Self::GetTvShowDetail { .. } | Self::GetTvSeason { .. } => DAY,
+            Self::GetTvExternalIds { .. } => 30 * DAY,
+            // image sizes and base URL change very rarely
+            Self::GetConfiguration => 30 * DAY,
+            Self::GetMovieImages { .. } => 7 * DAY,
+            Self::GetMovieCredits { .. } => 7 * DAY,
+        }
+    }
+
+    /// Returns path and query without API key
+    fn parts(&'a self) -> (String, QueryBuilder<'a>) {
+        match self {
+            Self::GetMovieDetail { movie_id } => (movie_path(*movie_id), QueryBuilder::new()),
             Self::SearchMovies { query, year } => {
-                let mut builder = QueryBuilder::new()
-                    .add("api_key", api_key)
-                    .add("query", query);
+                let mut builder = QueryBuilder::new().add("query", query);
 
                 if let Some(year) = year {
                     builder = builder.add("primary_release_year", year);
                 }
 
-                let query = builder.build();
-                build_url("/search/movie", query)
+                ("/search/movie".into(), builder)
             }
+            Self::FindByExternalId { external_id } => {
+                let (id, source) = match external_id {
+                    ExternalId::Imdb(id) => (id.clone(), "imdb_id"),
+                    ExternalId::TheTvDb(id) => (id.to_string(), "tvdb_id"),
+                };
+
+                (
+                    format!("/find/{id}"),
+                    QueryBuilder::new().add("external_source", source),
+                )
+            }
+            Self::SearchTvShows { query, year } => {
+                let mut builder = QueryBuilder::new().add("query", query);
+
+                if let Some(year) = year {
+                    builder = builder.add("first_air_date_year", year);
+                }
+
+                ("/search/tv".into(), builder)
+            }
+            Self::GetTvShowDetail { tvshow_id } => (tv_path(*tvshow_id), QueryBuilder::new()),
+            Self::GetTvSeason {
+                tvshow_id,
+                season_number,
+            } => (
+                format!("{}/season/{season_number}", tv_path(*tvshow_id)),
+                QueryBuilder::new(),
+            ),
+            Self::GetTvExternalIds { tvshow_id } => (
+                format!("{}/external_ids", tv_path(*tvshow_id)),
+                QueryBuilder::new(),
+            ),
+            Self::GetConfiguration => ("/configuration".into(), QueryBuilder::new()),
+            Self::GetMovieImages { movie_id } => (
+                format!("{}/images", movie_path(*movie_id)),
+                QueryBuilder::new(),
+            ),
+            Self::GetMovieCredits { movie_id } => (
+                format!("{}/credits", movie_path(*movie_id)),
+                QueryBuilder::new(),
+            ),
         }
     }
 }
 
-fn build_url(path: impl Display, query: String) -> String {
-    format!("{BASE_URL}{path}{query}")
+/// Path of movie, all cached responses about the movie start with it
+pub fn movie_path(movie_id: usize) -> String {
+    format!("/movie/{movie_id}")
+}
+
+/// Path of TV show, all cached responses about the show start with it
+pub fn tv_path(tvshow_id: usize) -> String {
+    format!("/tv/{tvshow_id}")
 }
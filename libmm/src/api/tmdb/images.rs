@@ -0,0 +1,170 @@
+use crate::db::artwork::ArtworkKind;
+use serde::Deserialize;
+
+/// Where and in which sizes TMDB serves images
+#[derive(Deserialize, Debug, Clone)]
+pub struct ImageConfiguration {
+    #[serde(rename = "secure_base_url")]
+    pub base_url: String,
+    pub poster_sizes: Vec<String>,
+    pub backdrop_sizes: Vec<String>,
+    pub logo_sizes: Vec<String>,
+}
+
+impl ImageConfiguration {
+    /// Returns URL of image in given size, like `w500` or `original`
+    pub fn url(&self, image: &Image, size: &str) -> String {
+        format!(
+            "{}/{size}/{}",
+            self.base_url.trim_end_matches('/'),
+            image.file_path.trim_start_matches('/')
+        )
+    }
+
+    /// Largest size, which is not wider than `max_width`, `original` when there is none
+    pub fn size(&self, kind: ArtworkKind, max_width: u32) -> &str {
+        let sizes = match kind {
+            ArtworkKind::Poster => &self.poster_sizes,
+            ArtworkKind::Backdrop => &self.backdrop_sizes,
+            ArtworkKind::Logo => &self.logo_sizes,
+        };
+
+        sizes
+            .iter()
+            .filter_map(|s| Some((s, s.strip_prefix('w')?.parse::<u32>().ok()?)))
+            .filter(|(_, width)| *width <= max_width)
+            .max_by_key(|(_, width)| *width)
+            .map(|(s, _)| s.as_str())
+            .unwrap_or("original")
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct ConfigurationResponse {
+    pub images: ImageConfiguration,
+}
+
+/// All artwork of movie available in TMDB
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct MovieImages {
+    #[serde(default)]
+    pub posters: Vec<Image>,
+    #[serde(default)]
+    pub backdrops: Vec<Image>,
+    #[serde(default)]
+    pub logos: Vec<Image>,
+}
+
+impl MovieImages {
+    pub fn images(&self, kind: ArtworkKind) -> &[Image] {
+        match kind {
+            ArtworkKind::Poster => &self.posters,
+            ArtworkKind::Backdrop => &self.backdrops,
+            ArtworkKind::Logo => &self.logos,
+        }
+    }
+
+    /// Chooses best rated image in `language`.
+    ///
+    /// Backdrops are preferred without text, posters and logos fall back to images without text.
+    pub fn best(&self, kind: ArtworkKind, language: &str) -> Option<&Image> {
+        let rank = |image: &Image| match (kind, image.language.as_deref()) {
+            (ArtworkKind::Backdrop, None) => 3,
+            (_, Some(l)) if l == language => 2,
+            (_, None) => 1,
+            _ => 0,
+        };
+
+        self.images(kind).iter().max_by(|a, b| {
+            rank(a)
+                .cmp(&rank(b))
+                .then(a.vote_average.total_cmp(&b.vote_average))
+                .then(a.width.cmp(&b.width))
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Image {
+    pub file_path: String,
+    /// ISO 639-1 code of language of text in image, `None` for images without text
+    #[serde(rename = "iso_639_1")]
+    pub language: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub vote_average: f64,
+}
+
+impl Image {
+    /// Extension of image file, like `jpg`
+    pub fn extension(&self) -> &str {
+        self.file_path
+            .rsplit_once('.')
+            .map(|(_, ext)| ext)
+            .unwrap_or("jpg")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(file_path: &str, language: Option<&str>, vote_average: f64) -> Image {
+        Image {
+            file_path: file_path.into(),
+            language: language.map(|l| l.into()),
+            width: 1000,
+            height: 1500,
+            vote_average,
+        }
+    }
+
+    #[test]
+    fn it_chooses_best_image() {
+        let images = MovieImages {
+            posters: vec![
+                image("/fr.jpg", Some("fr"), 5.8),
+                image("/en.jpg", Some("en"), 5.3),
+                image("/textless.jpg", None, 5.5),
+            ],
+            backdrops: vec![
+                image("/en.jpg", Some("en"), 5.8),
+                image("/textless.jpg", None, 5.2),
+            ],
+            logos: vec![image("/fr.png", Some("fr"), 5.0)],
+        };
+
+        let poster = images.best(ArtworkKind::Poster, "en").unwrap();
+        assert_eq!(poster.file_path, "/en.jpg");
+        let poster = images.best(ArtworkKind::Poster, "de").unwrap();
+        assert_eq!(poster.file_path, "/textless.jpg");
+        let backdrop = images.best(ArtworkKind::Backdrop, "en").unwrap();
+        assert_eq!(backdrop.file_path, "/textless.jpg");
+        let logo = images.best(ArtworkKind::Logo, "en").unwrap();
+        assert_eq!(logo.extension(), "png");
+    }
+
+    #[test]
+    fn it_builds_image_url() {
+        let config = ImageConfiguration {
+            base_url: "https://image.tmdb.org/t/p/".into(),
+            poster_sizes: vec![
+                "w92".into(),
+                "w500".into(),
+                "w780".into(),
+                "original".into(),
+            ],
+            backdrop_sizes: vec!["w300".into(), "w1280".into(), "original".into()],
+            logo_sizes: vec![],
+        };
+
+        assert_eq!(config.size(ArtworkKind::Poster, 600), "w500");
+        assert_eq!(config.size(ArtworkKind::Backdrop, 100), "original");
+        assert_eq!(config.size(ArtworkKind::Logo, 500), "original");
+        assert_eq!(
+            config.url(&image("/abc.jpg", None, 0.0), "w500"),
+            "https://image.tmdb.org/t/p/w500/abc.jpg"
+        );
+    }
+}
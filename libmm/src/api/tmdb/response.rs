@@ -1,18 +1,23 @@
+use crate::api::tmdb::tv::TmdbTvShow;
+use crate::db::genre::Genre;
 use crate::db::movie::IncompleteMovie;
+use crate::db::person::{Credit, IncompleteCredit, IncompletePerson, ACTOR};
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct MovieDetail {
     pub id: usize,
     pub title: String,
-    #[allow(dead_code)]
     pub original_title: String,
-    #[allow(dead_code)]
     pub original_language: String,
-    #[allow(dead_code)]
     pub overview: Option<String>,
     pub release_date: String,
     pub runtime: u32,
+    pub tagline: Option<String>,
+    pub imdb_id: Option<String>,
+    pub vote_average: Option<f64>,
+    #[serde(default)]
+    pub genres: Vec<GenreDetail>,
 }
 
 impl From<MovieDetail> for IncompleteMovie {
@@ -23,10 +28,75 @@ impl From<MovieDetail> for IncompleteMovie {
             movie.set_runtime(Some(md.runtime));
         }
 
+        movie.original_title = Some(md.original_title);
+        movie.original_language = Some(md.original_language);
+        movie.overview = md.overview.filter(|o| !o.is_empty());
+        movie.tagline = md.tagline.filter(|t| !t.is_empty());
+        movie.imdb_id = md.imdb_id.filter(|i| !i.is_empty());
+        movie.vote_average = md.vote_average;
+        movie.genres = md.genres.into_iter().map(|g| g.into()).collect();
+
         movie
     }
 }
 
+#[derive(Deserialize, Debug)]
+pub(crate) struct GenreDetail {
+    pub id: usize,
+    pub name: String,
+}
+
+impl From<GenreDetail> for Genre {
+    fn from(genre: GenreDetail) -> Self {
+        Genre {
+            tmdb_id: genre.id,
+            name: genre.name,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct CreditsResponse {
+    #[serde(default)]
+    pub cast: Vec<CastMember>,
+    #[serde(default)]
+    pub crew: Vec<CrewMember>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct CastMember {
+    pub id: usize,
+    pub name: String,
+    pub character: Option<String>,
+    pub order: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct CrewMember {
+    pub id: usize,
+    pub name: String,
+    pub job: String,
+}
+
+impl From<CreditsResponse> for Vec<IncompleteCredit> {
+    fn from(credits: CreditsResponse) -> Self {
+        let cast = credits.cast.into_iter().map(|c| Credit {
+            person: IncompletePerson::new(c.id, c.name),
+            role: ACTOR.into(),
+            character: c.character.filter(|c| !c.is_empty()),
+            order: c.order,
+        });
+        let crew = credits.crew.into_iter().map(|c| Credit {
+            person: IncompletePerson::new(c.id, c.name),
+            role: c.job,
+            character: None,
+            order: None,
+        });
+
+        cast.chain(crew).collect()
+    }
+}
+
 /// Convert from yyyy-mm-dd to year integer
 fn convert_year(date: String) -> u32 {
     date.split('-')
@@ -51,21 +121,36 @@ pub(crate) struct SearchMovieResponse {
 pub(crate) struct SearchedMovie {
     pub id: usize,
     pub title: String,
-    #[allow(dead_code)]
     pub original_title: String,
-    #[allow(dead_code)]
     pub original_language: String,
-    #[allow(dead_code)]
     pub overview: Option<String>,
     pub release_date: String,
 }
 
 impl From<SearchedMovie> for IncompleteMovie {
     fn from(sm: SearchedMovie) -> Self {
-        IncompleteMovie::new(sm.id, sm.title, convert_year(sm.release_date))
+        let mut movie = IncompleteMovie::new(sm.id, sm.title, convert_year(sm.release_date));
+
+        movie.original_title = Some(sm.original_title);
+        movie.original_language = Some(sm.original_language);
+        movie.overview = sm.overview.filter(|o| !o.is_empty());
+
+        movie
     }
 }
 
+#[derive(Deserialize, Default)]
+pub(crate) struct FindResponse {
+    pub movie_results: Vec<SearchedMovie>,
+    #[serde(default)]
+    pub tv_results: Vec<TmdbTvShow>,
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) struct SearchTvResponse {
+    pub results: Vec<TmdbTvShow>,
+}
+
 #[derive(Deserialize, Debug)]
 pub(crate) struct ErrorInfo {
     #[allow(dead_code)]
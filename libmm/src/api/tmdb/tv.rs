@@ -0,0 +1,128 @@
+use crate::api::ExternalId;
+use crate::db::tvshow::IncompleteTvShow;
+use serde::{Deserialize, Deserializer};
+
+/// TV show as described by TMDB.
+///
+/// Episodes of shows in database are tracked by TVMaze ids, so it is converted to [`IncompleteTvShow`]
+/// only together with id of the same show in TVMaze.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TmdbTvShow {
+    #[serde(rename = "id")]
+    pub tmdb_id: usize,
+    #[serde(rename = "name")]
+    pub title: String,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub first_air_date: Option<String>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub last_air_date: Option<String>,
+    /// Only known in detail, not in search results
+    #[serde(default)]
+    pub in_production: Option<bool>,
+    /// Seasons without episodes, only in detail
+    #[serde(default)]
+    pub seasons: Vec<TmdbSeason>,
+}
+
+impl TmdbTvShow {
+    pub fn into_tvshow(self, tvmaze_id: usize) -> IncompleteTvShow {
+        let mut tvshow = IncompleteTvShow::new(tvmaze_id, self.title);
+        tvshow.tmdb_id = Some(self.tmdb_id);
+        tvshow.premiered = self.first_air_date;
+        if self.in_production == Some(false) {
+            tvshow.ended = self.last_air_date;
+        }
+
+        tvshow
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TmdbSeason {
+    #[serde(rename = "id")]
+    pub tmdb_id: usize,
+    #[serde(rename = "season_number")]
+    pub number: u32,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub air_date: Option<String>,
+    /// Only filled when season is requested alone
+    #[serde(default)]
+    pub episodes: Vec<TmdbEpisode>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TmdbEpisode {
+    #[serde(rename = "id")]
+    pub tmdb_id: usize,
+    pub season_number: u32,
+    #[serde(rename = "episode_number")]
+    pub number: u32,
+    #[serde(rename = "name")]
+    pub title: String,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub air_date: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct ExternalIds {
+    #[serde(default, deserialize_with = "non_empty")]
+    imdb_id: Option<String>,
+    tvdb_id: Option<usize>,
+}
+
+impl From<ExternalIds> for Vec<ExternalId> {
+    fn from(ids: ExternalIds) -> Self {
+        ids.imdb_id
+            .map(ExternalId::Imdb)
+            .into_iter()
+            .chain(ids.tvdb_id.map(ExternalId::TheTvDb))
+            .collect()
+    }
+}
+
+/// TMDB uses both `null` and empty string for unknown values
+fn non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.filter(|s| !s.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_tvshow() {
+        let json = r#"{"id": 1399, "name": "Game of Thrones", "first_air_date": "2011-04-17", "last_air_date": "2019-05-19", "in_production": false, "seasons": [{"id": 3624, "season_number": 1, "air_date": "2011-04-17", "episode_count": 10}]}"#;
+
+        let detail = serde_json::from_str::<TmdbTvShow>(json).unwrap();
+        assert_eq!(detail.seasons.len(), 1);
+        assert_eq!(detail.seasons[0].number, 1);
+
+        let tvshow = detail.into_tvshow(82);
+        assert_eq!(tvshow.tvmaze_id, 82);
+        assert_eq!(tvshow.tmdb_id, Some(1399));
+        assert_eq!(tvshow.premiered, Some("2011-04-17".into()));
+        assert_eq!(tvshow.ended, Some("2019-05-19".into()));
+    }
+
+    #[test]
+    fn it_ignores_empty_values() {
+        let json = r#"{"id": 1, "name": "Unknown", "first_air_date": ""}"#;
+
+        let tvshow = serde_json::from_str::<TmdbTvShow>(json).unwrap();
+        assert_eq!(tvshow.first_air_date, None);
+
+        let json = r#"{"id": 1399, "imdb_id": "tt0944947", "tvdb_id": 121361, "tvrage_id": 24493}"#;
+        let ids: Vec<ExternalId> = serde_json::from_str::<ExternalIds>(json).unwrap().into();
+        assert_eq!(
+            ids,
+            vec![
+                ExternalId::Imdb("tt0944947".into()),
+                ExternalId::TheTvDb(121361)
+            ]
+        );
+
+        let json = r#"{"id": 1, "imdb_id": "", "tvdb_id": null}"#;
+        let ids: Vec<ExternalId> = serde_json::from_str::<ExternalIds>(json).unwrap().into();
+        assert!(ids.is_empty());
+    }
+}
@@ -1,43 +1,168 @@
-use crate::api::tvmaze::endpoint::TvMazeEndpoint;
-use crate::api::tvmaze::response::SearchTvShowResponse;
+use crate::api::executor::{RateLimit, RequestExecutor};
+use crate::api::tvmaze::endpoint::{tvshow_path, TvMazeEndpoint, DEFAULT_BASE_URL};
+use crate::api::tvmaze::response::{
+    EpisodeDetail, SearchTvShowResponse, SeasonDetail, TvShowDetail,
+};
+use crate::api::{parse_json, ExternalId, MetadataProvider, ResponseCache, RetryPolicy};
+use crate::db::episode::IncompleteEpisode;
+use crate::db::season::IncompleteSeason;
 use crate::db::tvshow::IncompleteTvShow;
 use crate::error::{ApiError, Error};
 
-use ureq::Agent;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
 
 mod endpoint;
 mod response;
 
+/// TVMaze allows at least 20 requests every 10 seconds
+const RATE_LIMIT: RateLimit = RateLimit {
+    requests: 20,
+    period: Duration::from_secs(10),
+};
+
+/// Client for the TVMaze API
 pub struct TvMazeClient {
-    agent: Agent,
+    base_url: String,
+    executor: RequestExecutor,
+    cache: Option<ResponseCache>,
+}
+
+impl Default for TvMazeClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TvMazeClient {
     pub fn new() -> Self {
         Self {
-            agent: Agent::new(),
+            base_url: DEFAULT_BASE_URL.into(),
+            executor: RequestExecutor::new(RATE_LIMIT),
+            cache: None,
         }
     }
 
+    /// Sends requests to another server, like a local mock of the API
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').into();
+        self
+    }
+
+    /// Changes how failed requests are retried
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.executor.set_retry_policy(retry);
+        self
+    }
+
+    /// Reuses responses stored in `cache` and stores new ones there
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     pub fn search_tvshows_by_title(
         &self,
         title: impl AsRef<str>,
     ) -> Result<Vec<IncompleteTvShow>, Error> {
-        let url = TvMazeEndpoint::SearchTvShow {
+        let endpoint = TvMazeEndpoint::SearchTvShow {
             query: title.as_ref(),
+        };
+
+        let results = self.get::<Vec<SearchTvShowResponse>>(&endpoint)?;
+
+        Ok(results
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| s.show.into())
+            .collect())
+    }
+
+    pub fn get_tvshow_detail(&self, tvshow_id: usize) -> Result<Option<IncompleteTvShow>, Error> {
+        let endpoint = TvMazeEndpoint::GetTvShow { tvshow_id };
+
+        Ok(self.get::<TvShowDetail>(&endpoint)?.map(|s| s.into()))
+    }
+
+    /// Finds TV show by its id in another database
+    pub fn lookup_tvshow(
+        &self,
+        external_id: &ExternalId,
+    ) -> Result<Option<IncompleteTvShow>, Error> {
+        let endpoint = TvMazeEndpoint::LookupTvShow { external_id };
+
+        Ok(self.get::<TvShowDetail>(&endpoint)?.map(|s| s.into()))
+    }
+
+    pub fn get_seasons(&self, tvshow_id: usize) -> Result<Vec<IncompleteSeason>, Error> {
+        let endpoint = TvMazeEndpoint::GetSeasons { tvshow_id };
+
+        let seasons = self.get::<Vec<SeasonDetail>>(&endpoint)?;
+
+        Ok(seasons
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| s.into())
+            .collect())
+    }
+
+    /// Returns list of episodes of all seasons.
+    ///
+    /// ## Remarks
+    /// Special episodes are not included.
+    pub fn get_episodes(&self, tvshow_id: usize) -> Result<Vec<IncompleteEpisode>, Error> {
+        let endpoint = TvMazeEndpoint::GetEpisodes { tvshow_id };
+
+        let episodes = self.get::<Vec<EpisodeDetail>>(&endpoint)?;
+
+        Ok(episodes
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(EpisodeDetail::into_episode)
+            .collect())
+    }
+
+    /// Removes all cached responses about the TV show, including its seasons and episodes
+    pub fn refresh_tvshow_detail(
+        &self,
+        tvshow_id: usize,
+    ) -> Result<Option<IncompleteTvShow>, Error> {
+        if let Some(cache) = &self.cache {
+            if cache.is_offline() {
+                return Err(ApiError::Offline.into());
+            }
+
+            cache.invalidate(&format!("tvmaze:{}", tvshow_path(tvshow_id)))?;
+        }
+
+        self.get_tvshow_detail(tvshow_id)
+    }
+
+    /// Returns `None` when the resource does not exist, only successful responses are cached
+    fn get<T: DeserializeOwned>(&self, endpoint: &TvMazeEndpoint) -> Result<Option<T>, Error> {
+        let key = endpoint.cache_key();
+
+        if let Some(cache) = &self.cache {
+            if let Some(body) = cache.get(&key, endpoint.ttl())? {
+                return parse_json(&body).map(Some);
+            }
         }
-        .url();
 
-        let res = self.agent.get(&url).call();
+        let res = self.executor.get(&endpoint.url(&self.base_url));
 
-        match res {
+        match res.map_err(|e| *e) {
             Ok(res) => {
-                let vec = res.into_json::<Vec<SearchTvShowResponse>>();
-                match vec {
-                    Ok(vec) => Ok(vec.into_iter().map(|s| s.show.into()).collect()),
-                    Err(_e) => Err(ApiError::InvalidFormat.into()),
+                let body = res.into_string().map_err(|_| ApiError::InvalidFormat)?;
+                let value = parse_json(&body)?;
+
+                if let Some(cache) = &self.cache {
+                    cache.insert(&key, &body)?;
                 }
+
+                Ok(Some(value))
             }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(ureq::Error::Status(429, _)) => Err(ApiError::RateLimited.into()),
             Err(ureq::Error::Status(_status, res)) => {
                 let res = res.into_string();
                 match res {
@@ -45,7 +170,53 @@ impl TvMazeClient {
                     Err(_e) => Err(ApiError::InvalidFormat.into()),
                 }
             }
-            Err(ureq::Error::Transport(t)) => Err(ApiError::Transport(t).into()),
+            Err(ureq::Error::Transport(t)) => Err(ApiError::from(t).into()),
+        }
+    }
+}
+
+impl MetadataProvider<IncompleteTvShow> for TvMazeClient {
+    fn name(&self) -> &'static str {
+        "TVMaze"
+    }
+
+    /// TVMaze can not search by year, so results are filtered by year of premiere
+    fn search(&self, title: &str, year: Option<usize>) -> Result<Vec<IncompleteTvShow>, Error> {
+        let mut results = self.search_tvshows_by_title(title)?;
+
+        if let Some(year) = year {
+            results.retain(|s| {
+                s.premiered
+                    .as_deref()
+                    .is_some_and(|p| p.starts_with(&year.to_string()))
+            });
         }
+
+        Ok(results)
+    }
+
+    fn id(&self, title: &IncompleteTvShow) -> String {
+        title.tvmaze_id.to_string()
+    }
+
+    fn detail(&self, id: &str) -> Result<Option<IncompleteTvShow>, Error> {
+        match id.parse() {
+            Ok(id) => self.get_tvshow_detail(id),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn refresh(&self, id: &str) -> Result<Option<IncompleteTvShow>, Error> {
+        match id.parse() {
+            Ok(id) => self.refresh_tvshow_detail(id),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn find_by_external_id(
+        &self,
+        external_id: &ExternalId,
+    ) -> Result<Option<IncompleteTvShow>, Error> {
+        self.lookup_tvshow(external_id)
     }
 }
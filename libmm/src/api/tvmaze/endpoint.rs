@@ -1,22 +1,71 @@
-use crate::api::QueryBuilder;
+use crate::api::{ExternalId, QueryBuilder};
 use std::fmt::Display;
+use std::time::Duration;
 
-const BASE_URL: &str = "https://api.tvmaze.com";
+/// Used when client is not given another base URL
+pub(super) const DEFAULT_BASE_URL: &str = "https://api.tvmaze.com";
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
 
 pub enum TvMazeEndpoint<'a> {
     SearchTvShow { query: &'a str },
+    GetTvShow { tvshow_id: usize },
+    GetEpisodes { tvshow_id: usize },
+    GetSeasons { tvshow_id: usize },
+    LookupTvShow { external_id: &'a ExternalId },
 }
 
 impl<'a> TvMazeEndpoint<'a> {
-    pub fn url(&'a self) -> String {
+    pub fn url(&'a self, base_url: &str) -> String {
         match self {
-            TvMazeEndpoint::SearchTvShow { query } => {
-                build_url("/search/shows", QueryBuilder::new().add("q", query).build())
+            TvMazeEndpoint::SearchTvShow { query } => build_url(
+                base_url,
+                "/search/shows",
+                QueryBuilder::new().add("q", query).build(),
+            ),
+            TvMazeEndpoint::GetTvShow { tvshow_id } => {
+                build_url(base_url, tvshow_path(*tvshow_id), String::new())
+            }
+            TvMazeEndpoint::GetEpisodes { tvshow_id } => build_url(
+                base_url,
+                format!("/shows/{tvshow_id}/episodes"),
+                String::new(),
+            ),
+            TvMazeEndpoint::GetSeasons { tvshow_id } => build_url(
+                base_url,
+                format!("/shows/{tvshow_id}/seasons"),
+                String::new(),
+            ),
+            TvMazeEndpoint::LookupTvShow { external_id } => {
+                let query = match external_id {
+                    ExternalId::Imdb(id) => QueryBuilder::new().add("imdb", id),
+                    ExternalId::TheTvDb(id) => QueryBuilder::new().add("thetvdb", id),
+                };
+
+                build_url(base_url, "/lookup/shows", query.build())
             }
         }
     }
+
+    /// Identifies response in cache
+    pub fn cache_key(&'a self) -> String {
+        format!("tvmaze:{}", self.url(""))
+    }
+
+    /// How long is cached response considered fresh, running shows change often
+    pub fn ttl(&self) -> Duration {
+        match self {
+            Self::LookupTvShow { .. } => 30 * DAY,
+            _ => DAY,
+        }
+    }
+}
+
+/// Path of TV show, all cached responses about the show start with it
+pub fn tvshow_path(tvshow_id: usize) -> String {
+    format!("/shows/{tvshow_id}")
 }
 
-fn build_url(path: impl Display, query: String) -> String {
-    format!("{BASE_URL}{path}{query}")
+fn build_url(base_url: &str, path: impl Display, query: String) -> String {
+    format!("{base_url}{path}{query}")
 }
@@ -1,21 +1,97 @@
+use crate::db::episode::IncompleteEpisode;
+use crate::db::season::IncompleteSeason;
 use crate::db::tvshow::IncompleteTvShow;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct SearchTvShowResponse {
-    pub(crate) show: SearchTvShowPartial,
+    pub(crate) show: TvShowDetail,
 }
 
 #[derive(Deserialize, Debug)]
-pub(crate) struct SearchTvShowPartial {
+pub(crate) struct TvShowDetail {
     id: usize,
     name: String,
-    premiered: String,
+    premiered: Option<String>,
     ended: Option<String>,
 }
 
-impl From<SearchTvShowPartial> for IncompleteTvShow {
-    fn from(tvshow: SearchTvShowPartial) -> Self {
-        IncompleteTvShow::new(tvshow.id, tvshow.name)
+impl From<TvShowDetail> for IncompleteTvShow {
+    fn from(tvshow: TvShowDetail) -> Self {
+        let mut show = IncompleteTvShow::new(tvshow.id, tvshow.name);
+        show.premiered = tvshow.premiered;
+        show.ended = tvshow.ended;
+
+        show
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct SeasonDetail {
+    id: usize,
+    number: u32,
+}
+
+impl From<SeasonDetail> for IncompleteSeason {
+    fn from(season: SeasonDetail) -> Self {
+        IncompleteSeason::new(season.id, season.number)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct EpisodeDetail {
+    id: usize,
+    name: String,
+    season: u32,
+    /// Special episodes have no number
+    number: Option<u32>,
+    airdate: Option<String>,
+}
+
+impl EpisodeDetail {
+    /// Returns `None` for special episodes, which are not numbered
+    pub(crate) fn into_episode(self) -> Option<IncompleteEpisode> {
+        let mut episode = IncompleteEpisode::new(self.id, self.season, self.number?, self.name);
+        // TVMaze uses empty string for unknown dates
+        episode.airdate = self.airdate.filter(|date| !date.is_empty());
+
+        Some(episode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_episodes() {
+        let json = r#"[
+            {"id": 4952, "name": "Winter Is Coming", "season": 1, "number": 1, "airdate": "2011-04-17", "runtime": 60},
+            {"id": 1221415, "name": "Inside the Episode", "season": 1, "number": null, "airdate": "", "runtime": 5}
+        ]"#;
+
+        let episodes = serde_json::from_str::<Vec<EpisodeDetail>>(json)
+            .unwrap()
+            .into_iter()
+            .filter_map(EpisodeDetail::into_episode)
+            .collect::<Vec<_>>();
+
+        assert_eq!(episodes.len(), 1);
+        assert_eq!(episodes[0].tvmaze_id, 4952);
+        assert_eq!(episodes[0].season_number, 1);
+        assert_eq!(episodes[0].number, 1);
+        assert_eq!(episodes[0].title, "Winter Is Coming");
+        assert_eq!(episodes[0].airdate, Some("2011-04-17".into()));
+    }
+
+    #[test]
+    fn it_keeps_tvshow_dates() {
+        let json = r#"{"id": 82, "name": "Game of Thrones", "premiered": "2011-04-17", "ended": "2019-05-19"}"#;
+
+        let tvshow: IncompleteTvShow = serde_json::from_str::<TvShowDetail>(json).unwrap().into();
+
+        assert_eq!(tvshow.tvmaze_id, 82);
+        assert_eq!(tvshow.premiered, Some("2011-04-17".into()));
+        assert_eq!(tvshow.ended, Some("2019-05-19".into()));
     }
 }
@@ -1,11 +1,24 @@
+use crate::db::artwork::Artwork;
+use crate::db::episode::Episode;
+use crate::db::genre::Genre;
 use crate::db::movie::Movie;
+use crate::db::person::Person;
+use crate::db::season::Season;
 use crate::db::tvshow::TvShow;
 use crate::error::{DbError, Error};
+use crate::media::ReleaseInfo;
 use crate::Loaded;
 use rusqlite::{Connection, Row};
 use std::path::Path;
 
+pub mod artwork;
+pub mod episode;
+pub mod genre;
+pub mod migration;
 pub mod movie;
+pub mod person;
+pub mod release;
+pub mod season;
 pub mod tvshow;
 
 #[derive(Debug)]
@@ -15,24 +28,52 @@ pub struct Database {
 
 impl Database {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let conn = Connection::open(path)?;
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
 
-        Self::init(&conn)?;
+    fn from_connection(mut conn: Connection) -> Result<Self, Error> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        Self::init(&mut conn)?;
 
         Ok(Self { conn })
     }
 
+    /// Returns version of the database schema
+    pub fn schema_version(&self) -> Result<u32, Error> {
+        migration::schema_version(&self.conn)
+    }
+
     pub(crate) fn last_insert_id(&self) -> Result<usize, Error> {
         self.conn
             .query_row("SELECT last_insert_rowid();", [], Self::get_first_row)
             .map_err(|e| e.into())
     }
 
-    fn init(conn: &Connection) -> Result<(), Error> {
-        conn.execute_batch(<Database as Creatable<Movie<Loaded>>>::create_table_sql())?;
-        conn.execute_batch(<Database as Creatable<TvShow<Loaded>>>::create_table_sql())?;
+    /// Creates the latest schema in empty databases, migrates existing ones.
+    fn init(conn: &mut Connection) -> Result<(), Error> {
+        let version = migration::schema_version(conn)?;
+
+        if version == 0 && Self::is_conn_empty(conn)? {
+            let tx = conn.transaction()?;
+            tx.execute_batch(<Database as Creatable<Movie<Loaded>>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<TvShow<Loaded>>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<Season<Loaded>>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<Episode<Loaded>>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<Genre>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<Artwork>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<Person<Loaded>>>::create_table_sql())?;
+            tx.execute_batch(<Database as Creatable<ReleaseInfo>>::create_table_sql())?;
+            migration::set_schema_version(&tx, migration::LATEST_VERSION)?;
+            tx.commit()?;
 
-        Ok(())
+            return Ok(());
+        }
+
+        migration::migrate(conn, version)
     }
 
     fn is_conn_empty(conn: &Connection) -> Result<bool, DbError> {
@@ -59,3 +100,11 @@ pub trait Selectable<T> {
     fn select_by_id(&self, id: usize) -> Result<Option<T>, Error>;
     fn list_all(&self) -> Result<Vec<T>, Error>;
 }
+
+pub trait Updatable<T> {
+    fn update(&self, object: &T) -> Result<(), Error>;
+}
+
+pub trait Deletable<T> {
+    fn delete(&self, object: T) -> Result<(), Error>;
+}
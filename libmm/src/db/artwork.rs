@@ -0,0 +1,139 @@
+use crate::db::{Creatable, Database};
+use crate::error::Error;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, ToSql};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ArtworkKind {
+    Poster,
+    /// Also known as fanart
+    Backdrop,
+    Logo,
+}
+
+impl ArtworkKind {
+    pub const ALL: [ArtworkKind; 3] = [Self::Poster, Self::Backdrop, Self::Logo];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Poster => "poster",
+            Self::Backdrop => "backdrop",
+            Self::Logo => "logo",
+        }
+    }
+}
+
+impl Display for ArtworkKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql for ArtworkKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for ArtworkKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or(FromSqlError::InvalidType)
+    }
+}
+
+/// Locally stored image of movie
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Artwork {
+    pub kind: ArtworkKind,
+    pub path: PathBuf,
+}
+
+impl Creatable<Artwork> for Database {
+    fn create_table_sql() -> &'static str {
+        "CREATE TABLE IF NOT EXISTS `artwork` (
+            `movie_id` INTEGER NOT NULL REFERENCES `movie` (`id`) ON DELETE CASCADE,
+            `kind` TEXT NOT NULL,
+            `path` TEXT NOT NULL,
+            PRIMARY KEY (`movie_id`, `kind`)
+        );"
+    }
+}
+
+impl Database {
+    /// Stores path of artwork, replacing previous artwork of the same kind
+    pub fn set_movie_artwork(&self, movie_id: usize, artwork: &Artwork) -> Result<(), Error> {
+        self.conn.execute(
+            "INSERT INTO `artwork` (movie_id, kind, path) VALUES (?, ?, ?) ON CONFLICT (movie_id, kind) DO UPDATE SET path = excluded.path",
+            params![movie_id, artwork.kind, artwork.path.to_string_lossy()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns artwork of movie ordered by kind
+    pub fn select_movie_artwork(&self, movie_id: usize) -> Result<Vec<Artwork>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT `kind`, `path` FROM `artwork` WHERE `movie_id` = ?")?;
+
+        let mapped = stmt.query_map([movie_id], |row| {
+            Ok(Artwork {
+                kind: row.get(0)?,
+                path: PathBuf::from(row.get::<usize, String>(1)?),
+            })
+        })?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+        vec.sort_by_key(|a| ArtworkKind::ALL.iter().position(|k| *k == a.kind));
+
+        Ok(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::movie::{IncompleteMovie, LoadedMovie};
+    use crate::db::{Deletable, Insertable, Selectable};
+
+    #[test]
+    fn it_stores_artwork() {
+        let db = Database::open_in_memory().unwrap();
+        let movie = IncompleteMovie::new(603, "The Matrix".into(), 1999);
+        let id = db
+            .insert(movie.complete(PathBuf::from("/movies/matrix.mkv")))
+            .unwrap();
+
+        let backdrop = Artwork {
+            kind: ArtworkKind::Backdrop,
+            path: PathBuf::from("/movies/fanart.jpg"),
+        };
+        let poster = Artwork {
+            kind: ArtworkKind::Poster,
+            path: PathBuf::from("/artwork/603/poster.jpg"),
+        };
+        db.set_movie_artwork(id, &backdrop).unwrap();
+        db.set_movie_artwork(id, &poster).unwrap();
+        let poster = Artwork {
+            kind: ArtworkKind::Poster,
+            path: PathBuf::from("/movies/poster.jpg"),
+        };
+        db.set_movie_artwork(id, &poster).unwrap();
+
+        assert_eq!(db.select_movie_artwork(id).unwrap(), vec![poster, backdrop]);
+
+        let movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        db.delete(movie).unwrap();
+        assert!(db.select_movie_artwork(id).unwrap().is_empty());
+    }
+}
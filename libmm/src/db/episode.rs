@@ -0,0 +1,256 @@
+use crate::db::season::LoadedSeason;
+use crate::db::{Creatable, Database, Insertable, Selectable};
+use crate::error::Error;
+use crate::{Complete, EntityState, Incomplete, Loaded};
+use rusqlite::{params, OptionalExtension, Row};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+
+pub struct Episode<T: EntityState> {
+    // are everywhere
+    pub tvmaze_id: usize,
+    pub season_number: u32,
+    pub number: u32,
+    pub title: String,
+    pub airdate: Option<String>,
+    // on loaded + complete
+    tvshow_id: Option<usize>,
+    season_id: Option<usize>,
+    path: Option<PathBuf>,
+    // only on loaded
+    id: Option<usize>,
+
+    _marker: std::marker::PhantomData<T>,
+}
+
+pub type IncompleteEpisode = Episode<Incomplete>;
+pub type CompleteEpisode = Episode<Complete>;
+pub type LoadedEpisode = Episode<Loaded>;
+
+impl Episode<Incomplete> {
+    pub fn new(tvmaze_id: usize, season_number: u32, number: u32, title: String) -> Self {
+        Self {
+            tvmaze_id,
+            season_number,
+            number,
+            title,
+            airdate: None,
+            tvshow_id: None,
+            season_id: None,
+            path: None,
+            id: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn complete(self, season: &LoadedSeason, path: PathBuf) -> Episode<Complete> {
+        // change after https://github.com/rust-lang/rust/issues/86555 stabilises
+        Episode {
+            tvmaze_id: self.tvmaze_id,
+            season_number: season.number,
+            number: self.number,
+            title: self.title,
+            airdate: self.airdate,
+            tvshow_id: Some(*season.tvshow_id()),
+            season_id: Some(*season.id()),
+            path: Some(path),
+            id: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl Episode<Complete> {
+    pub fn tvshow_id(&self) -> &usize {
+        self.tvshow_id.as_ref().unwrap()
+    }
+
+    pub fn season_id(&self) -> &usize {
+        self.season_id.as_ref().unwrap()
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        self.path.as_ref().unwrap()
+    }
+}
+
+impl Episode<Loaded> {
+    pub fn id(&self) -> &usize {
+        self.id.as_ref().unwrap()
+    }
+
+    pub fn tvshow_id(&self) -> &usize {
+        self.tvshow_id.as_ref().unwrap()
+    }
+
+    pub fn season_id(&self) -> &usize {
+        self.season_id.as_ref().unwrap()
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        self.path.as_ref().unwrap()
+    }
+}
+
+impl<T: EntityState> Debug for Episode<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Episode")
+            .field("id", &self.id)
+            .field("tvshow_id", &self.tvshow_id)
+            .field("season_id", &self.season_id)
+            .field("tvmaze_id", &self.tvmaze_id)
+            .field("season_number", &self.season_number)
+            .field("number", &self.number)
+            .field("title", &self.title)
+            .field("airdate", &self.airdate)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl<T: EntityState> Creatable<Episode<T>> for Database {
+    fn create_table_sql() -> &'static str {
+        "CREATE TABLE IF NOT EXISTS `episode` (
+            `id` INTEGER PRIMARY KEY,
+            `tvshow_id` INTEGER NOT NULL REFERENCES `tvshow` (`id`) ON DELETE CASCADE,
+            `season_id` INTEGER NOT NULL REFERENCES `season` (`id`) ON DELETE CASCADE,
+            `tvmaze_id` INTEGER,
+            `number` INTEGER,
+            `title` TEXT,
+            `path` TEXT,
+            `airdate` TEXT
+        );"
+    }
+}
+
+impl Insertable<CompleteEpisode> for Database {
+    fn insert(&self, object: CompleteEpisode) -> Result<usize, Error> {
+        let Episode {
+            tvshow_id,
+            season_id,
+            tvmaze_id,
+            number,
+            title,
+            airdate,
+            path,
+            ..
+        } = object;
+
+        let mut stmt = self.conn.prepare(
+            "INSERT INTO `episode` (tvshow_id, season_id, tvmaze_id, number, title, path, airdate) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )?;
+
+        stmt.execute(params![
+            tvshow_id,
+            season_id,
+            tvmaze_id,
+            number,
+            title.as_str(),
+            path.unwrap().to_string_lossy(),
+            airdate
+        ])?;
+
+        Database::last_insert_id(self)
+    }
+}
+
+const SELECT_EPISODE: &str =
+    "SELECT e.id, e.tvshow_id, e.season_id, e.tvmaze_id, s.number, e.number, e.title, e.path, e.airdate
+    FROM `episode` e JOIN `season` s ON e.season_id = s.id";
+
+impl Selectable<LoadedEpisode> for Database {
+    fn select_by_id(&self, id: usize) -> Result<Option<LoadedEpisode>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{SELECT_EPISODE} WHERE e.id = ?"))?;
+
+        Ok(stmt.query_row([id], episode_mapper).optional()?)
+    }
+
+    fn list_all(&self) -> Result<Vec<LoadedEpisode>, Error> {
+        let mut stmt = self.conn.prepare(SELECT_EPISODE)?;
+
+        let mapped = stmt.query_map([], episode_mapper)?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+
+        Ok(vec)
+    }
+}
+
+fn episode_mapper(row: &Row) -> Result<LoadedEpisode, rusqlite::Error> {
+    Ok(Episode {
+        id: row.get(0)?,
+        tvshow_id: row.get(1)?,
+        season_id: row.get(2)?,
+        tvmaze_id: row.get(3)?,
+        season_number: row.get(4)?,
+        number: row.get(5)?,
+        title: row.get(6)?,
+        path: Some(PathBuf::from(row.get::<usize, String>(7)?)),
+        airdate: row.get(8)?,
+        _marker: std::marker::PhantomData,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::season::IncompleteSeason;
+    use crate::db::tvshow::{IncompleteTvShow, LoadedTvShow};
+    use crate::db::Deletable;
+
+    fn insert_season(db: &Database) -> LoadedSeason {
+        let tvshow_id = db
+            .insert(IncompleteTvShow::new(82, "Game of Thrones".into()).complete())
+            .unwrap();
+        let season_id = db
+            .insert(IncompleteSeason::new(307, 1).complete(tvshow_id))
+            .unwrap();
+
+        db.select_by_id(season_id).unwrap().unwrap()
+    }
+
+    #[test]
+    fn it_selects_episode() {
+        let db = Database::open_in_memory().unwrap();
+        let season = insert_season(&db);
+
+        let mut episode = IncompleteEpisode::new(4952, 1, 1, "Winter Is Coming".into());
+        episode.airdate = Some("2011-04-17".into());
+        let episode = episode.complete(&season, PathBuf::from("/shows/got/s01e01.mkv"));
+        let id = db.insert(episode).unwrap();
+
+        let episode: LoadedEpisode = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(episode.id(), &id);
+        assert_eq!(episode.tvshow_id(), season.tvshow_id());
+        assert_eq!(episode.season_id(), season.id());
+        assert_eq!(episode.tvmaze_id, 4952);
+        assert_eq!(episode.season_number, 1);
+        assert_eq!(episode.number, 1);
+        assert_eq!(episode.title, "Winter Is Coming");
+        assert_eq!(episode.airdate, Some("2011-04-17".into()));
+        assert_eq!(episode.path(), &PathBuf::from("/shows/got/s01e01.mkv"));
+    }
+
+    #[test]
+    fn it_deletes_episodes_with_tvshow() {
+        let db = Database::open_in_memory().unwrap();
+        let season = insert_season(&db);
+
+        let episode = IncompleteEpisode::new(4952, 1, 1, "Winter Is Coming".into())
+            .complete(&season, PathBuf::from("/shows/got/s01e01.mkv"));
+        db.insert(episode).unwrap();
+
+        let tvshow: LoadedTvShow = db.select_by_id(*season.tvshow_id()).unwrap().unwrap();
+        db.delete(tvshow).unwrap();
+
+        let seasons: Vec<LoadedSeason> = db.list_all().unwrap();
+        let episodes: Vec<LoadedEpisode> = db.list_all().unwrap();
+        assert!(seasons.is_empty());
+        assert!(episodes.is_empty());
+    }
+}
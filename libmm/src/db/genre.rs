@@ -0,0 +1,70 @@
+use crate::db::{Creatable, Database};
+use crate::error::Error;
+use rusqlite::params;
+
+/// Genre of movie, identified by its TMDB id
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Genre {
+    pub tmdb_id: usize,
+    pub name: String,
+}
+
+impl Creatable<Genre> for Database {
+    fn create_table_sql() -> &'static str {
+        "CREATE TABLE IF NOT EXISTS `genre` (
+            `id` INTEGER PRIMARY KEY,
+            `tmdb_id` INTEGER NOT NULL UNIQUE,
+            `name` TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS `movie_genre` (
+            `movie_id` INTEGER NOT NULL REFERENCES `movie` (`id`) ON DELETE CASCADE,
+            `genre_id` INTEGER NOT NULL REFERENCES `genre` (`id`) ON DELETE CASCADE,
+            PRIMARY KEY (`movie_id`, `genre_id`)
+        );"
+    }
+}
+
+impl Database {
+    /// Replaces genres of movie, genres are created when they are not stored yet
+    pub(crate) fn set_movie_genres(&self, movie_id: usize, genres: &[Genre]) -> Result<(), Error> {
+        self.conn
+            .execute("DELETE FROM `movie_genre` WHERE `movie_id` = ?", [movie_id])?;
+
+        let mut upsert = self.conn.prepare(
+            "INSERT INTO `genre` (tmdb_id, name) VALUES (?, ?) ON CONFLICT (tmdb_id) DO UPDATE SET name = excluded.name",
+        )?;
+        let mut link = self.conn.prepare(
+            "INSERT OR IGNORE INTO `movie_genre` (movie_id, genre_id) SELECT ?, `id` FROM `genre` WHERE `tmdb_id` = ?",
+        )?;
+
+        for genre in genres {
+            upsert.execute(params![genre.tmdb_id, genre.name])?;
+            link.execute(params![movie_id, genre.tmdb_id])?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn select_movie_genres(&self, movie_id: usize) -> Result<Vec<Genre>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT `genre`.`tmdb_id`, `genre`.`name` FROM `genre`
+            JOIN `movie_genre` ON `movie_genre`.`genre_id` = `genre`.`id`
+            WHERE `movie_genre`.`movie_id` = ?
+            ORDER BY `genre`.`name`",
+        )?;
+
+        let mapped = stmt.query_map([movie_id], |row| {
+            Ok(Genre {
+                tmdb_id: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+
+        Ok(vec)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::error::{DbError, Error};
+use rusqlite::Connection;
+
+/// Ordered list of schema migrations.
+///
+/// Migration at index `i` upgrades the schema from version `i` to version `i + 1`.
+/// Already released migrations must never be changed, only new ones appended.
+const MIGRATIONS: &[&str] = &[
+    // 1: schema of databases created before versioning was introduced
+    "CREATE TABLE IF NOT EXISTS `movie` (
+        `id` INTEGER PRIMARY KEY,
+        `tmdb_id` INTEGER,
+        `title` TEXT,
+        `cut` TEXT,
+        `path` TEXT,
+        `original_runtime` INTEGER,
+        `release_year` INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS `tvshow` (
+        `id` INTEGER PRIMARY KEY,
+        `tvmaze_id` INTEGER,
+        `title` TEXT
+    );",
+    // 2: seasons and episodes of tv shows
+    "CREATE TABLE `season` (
+        `id` INTEGER PRIMARY KEY,
+        `tvshow_id` INTEGER NOT NULL REFERENCES `tvshow` (`id`) ON DELETE CASCADE,
+        `tvmaze_id` INTEGER,
+        `number` INTEGER
+    );
+    CREATE TABLE `episode` (
+        `id` INTEGER PRIMARY KEY,
+        `tvshow_id` INTEGER NOT NULL REFERENCES `tvshow` (`id`) ON DELETE CASCADE,
+        `season_id` INTEGER NOT NULL REFERENCES `season` (`id`) ON DELETE CASCADE,
+        `tvmaze_id` INTEGER,
+        `number` INTEGER,
+        `title` TEXT,
+        `path` TEXT
+    );",
+    // 3: air dates of tv shows and episodes
+    "ALTER TABLE `tvshow` ADD COLUMN `premiered` TEXT;
+    ALTER TABLE `tvshow` ADD COLUMN `ended` TEXT;
+    ALTER TABLE `episode` ADD COLUMN `airdate` TEXT;",
+    // 4: release info parsed from filename
+    "CREATE TABLE `movie_release` (
+        `movie_id` INTEGER PRIMARY KEY REFERENCES `movie` (`id`) ON DELETE CASCADE,
+        `resolution` TEXT,
+        `source` TEXT,
+        `remux` INTEGER NOT NULL,
+        `video_codec` TEXT,
+        `audio` TEXT NOT NULL,
+        `hdr` TEXT NOT NULL,
+        `release_group` TEXT,
+        `languages` TEXT NOT NULL
+    );",
+    // 5: TMDB id of tv shows, for cross-referencing with TVMaze
+    "ALTER TABLE `tvshow` ADD COLUMN `tmdb_id` INTEGER;",
+    // 6: details of movies and their genres
+    "ALTER TABLE `movie` ADD COLUMN `original_title` TEXT;
+    ALTER TABLE `movie` ADD COLUMN `original_language` TEXT;
+    ALTER TABLE `movie` ADD COLUMN `overview` TEXT;
+    ALTER TABLE `movie` ADD COLUMN `tagline` TEXT;
+    ALTER TABLE `movie` ADD COLUMN `imdb_id` TEXT;
+    ALTER TABLE `movie` ADD COLUMN `vote_average` REAL;
+    CREATE TABLE `genre` (
+        `id` INTEGER PRIMARY KEY,
+        `tmdb_id` INTEGER NOT NULL UNIQUE,
+        `name` TEXT NOT NULL
+    );
+    CREATE TABLE `movie_genre` (
+        `movie_id` INTEGER NOT NULL REFERENCES `movie` (`id`) ON DELETE CASCADE,
+        `genre_id` INTEGER NOT NULL REFERENCES `genre` (`id`) ON DELETE CASCADE,
+        PRIMARY KEY (`movie_id`, `genre_id`)
+    );",
+    // 7: locally stored artwork of movies
+    "CREATE TABLE `artwork` (
+        `movie_id` INTEGER NOT NULL REFERENCES `movie` (`id`) ON DELETE CASCADE,
+        `kind` TEXT NOT NULL,
+        `path` TEXT NOT NULL,
+        PRIMARY KEY (`movie_id`, `kind`)
+    );",
+    // 8: cast and crew of movies
+    "CREATE TABLE `person` (
+        `id` INTEGER PRIMARY KEY,
+        `tmdb_id` INTEGER NOT NULL UNIQUE,
+        `name` TEXT NOT NULL
+    );
+    CREATE TABLE `movie_credit` (
+        `movie_id` INTEGER NOT NULL REFERENCES `movie` (`id`) ON DELETE CASCADE,
+        `person_id` INTEGER NOT NULL REFERENCES `person` (`id`) ON DELETE CASCADE,
+        `role` TEXT NOT NULL,
+        `character` TEXT,
+        `order` INTEGER,
+        PRIMARY KEY (`movie_id`, `person_id`, `role`)
+    );",
+    // 9: movies from providers other than TMDB
+    "ALTER TABLE `movie` ADD COLUMN `provider` TEXT NOT NULL DEFAULT 'tmdb';
+    ALTER TABLE `movie` ADD COLUMN `provider_id` TEXT NOT NULL DEFAULT '';
+    UPDATE `movie` SET `provider_id` = CAST(`tmdb_id` AS TEXT);",
+];
+
+/// Schema version this build of the library understands
+pub const LATEST_VERSION: u32 = MIGRATIONS.len() as u32;
+
+pub(crate) fn schema_version(conn: &Connection) -> Result<u32, Error> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+pub(crate) fn set_schema_version(conn: &Connection, version: u32) -> Result<(), Error> {
+    Ok(conn.pragma_update(None, "user_version", version)?)
+}
+
+/// Applies all migrations newer than `version`, each one in its own transaction.
+pub(crate) fn migrate(conn: &mut Connection, version: u32) -> Result<(), Error> {
+    if version > LATEST_VERSION {
+        return Err(DbError::UnsupportedSchema {
+            found: version,
+            supported: LATEST_VERSION,
+        }
+        .into());
+    }
+
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        set_schema_version(&tx, i as u32 + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn table_columns(conn: &Connection, table: &str) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info(`{table}`)"))
+            .unwrap();
+
+        stmt.query_map([], |row| Ok((row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|c| c.unwrap())
+            .collect()
+    }
+
+    fn table_names(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_schema WHERE type='table' ORDER BY name;")
+            .unwrap();
+
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|n| n.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn it_creates_latest_schema() {
+        let db = Database::open_in_memory().unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), LATEST_VERSION);
+    }
+
+    #[test]
+    fn it_migrates_unversioned_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.execute(
+            "INSERT INTO `movie` (tmdb_id, title, path, original_runtime, release_year) VALUES (1, 'Movie', '/movie.mkv', 90, 2000)",
+            [],
+        )
+        .unwrap();
+
+        let migrated = Database::from_connection(conn).unwrap();
+        let fresh = Database::open_in_memory().unwrap();
+
+        assert_eq!(migrated.schema_version().unwrap(), LATEST_VERSION);
+
+        let tables = table_names(&fresh.conn);
+        assert_eq!(table_names(&migrated.conn), tables);
+        for table in tables {
+            assert_eq!(
+                table_columns(&migrated.conn, &table),
+                table_columns(&fresh.conn, &table),
+                "schema of table `{table}` differs"
+            );
+        }
+
+        let (provider, provider_id): (String, String) = migrated
+            .conn
+            .query_row("SELECT `provider`, `provider_id` FROM `movie`", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(provider, "tmdb");
+        assert_eq!(provider_id, "1");
+    }
+
+    #[test]
+    fn it_refuses_newer_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        set_schema_version(&conn, LATEST_VERSION + 1).unwrap();
+
+        let res = Database::from_connection(conn);
+
+        assert!(matches!(
+            res,
+            Err(Error::Db(DbError::UnsupportedSchema { found, supported }))
+                if found == LATEST_VERSION + 1 && supported == LATEST_VERSION
+        ));
+    }
+}
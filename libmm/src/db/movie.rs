@@ -1,19 +1,39 @@
-use crate::db::{Creatable, Database, Insertable, Selectable};
-use crate::error::Error;
+use crate::db::genre::Genre;
+use crate::db::person::like_pattern;
+use crate::db::{Creatable, Database, Deletable, Insertable, Selectable, Updatable};
+use crate::error::{DbError, Error};
+use crate::media::ReleaseInfo;
 use crate::{Complete, EntityState, Incomplete, Loaded};
 use rusqlite::{params, OptionalExtension, Row};
 use std::fmt::{Debug, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Provider name of movies from TMDB
+pub const TMDB_PROVIDER: &str = "tmdb";
 
 pub struct Movie<T: EntityState> {
     // are everywhere
-    pub tmdb_id: usize,
+    /// Name of metadata provider, like `tmdb`
+    pub provider: String,
+    /// Id of movie at its provider
+    pub provider_id: String,
+    /// Set when details came from TMDB, which also provides artwork
+    pub tmdb_id: Option<usize>,
     pub title: String,
     pub release_year: u32,
+    // details, usually missing in search results
+    pub original_title: Option<String>,
+    pub original_language: Option<String>,
+    pub overview: Option<String>,
+    pub tagline: Option<String>,
+    pub imdb_id: Option<String>,
+    pub vote_average: Option<f64>,
+    pub genres: Vec<Genre>,
     // on loaded + complete
     path: Option<PathBuf>,
     original_runtime: Option<u32>, // might be on incomplete
     cut: Option<String>,
+    release: Option<ReleaseInfo>,
     // only on loaded
     id: Option<usize>,
 
@@ -28,27 +48,61 @@ impl<T: EntityState> Debug for Movie<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Movie")
             .field("id", &self.id)
+            .field("provider", &self.provider)
+            .field("provider_id", &self.provider_id)
             .field("tmdb_id", &self.tmdb_id)
             .field("title", &self.title)
-            .field("release_year", &self.title)
+            .field("release_year", &self.release_year)
+            .field("original_title", &self.original_title)
+            .field("original_language", &self.original_language)
+            .field("overview", &self.overview)
+            .field("tagline", &self.tagline)
+            .field("imdb_id", &self.imdb_id)
+            .field("vote_average", &self.vote_average)
+            .field("genres", &self.genres)
             .field("original_runtime", &self.original_runtime)
             .field("cut", &self.cut)
+            .field("release", &self.release)
             .field("path", &self.path)
             .finish()
     }
 }
 
 impl Movie<Incomplete> {
+    /// Creates movie with details from TMDB
     pub fn new(tmdb_id: usize, title: String, release_year: u32) -> Self {
+        let mut movie =
+            Self::with_provider(TMDB_PROVIDER, tmdb_id.to_string(), title, release_year);
+        movie.tmdb_id = Some(tmdb_id);
+        movie
+    }
+
+    /// Creates movie with details from any metadata provider
+    pub fn with_provider(
+        provider: &str,
+        provider_id: String,
+        title: String,
+        release_year: u32,
+    ) -> Self {
         Self {
-            tmdb_id,
+            provider: provider.to_string(),
+            provider_id,
+            tmdb_id: None,
             title,
             release_year,
+            original_title: None,
+            original_language: None,
+            overview: None,
+            tagline: None,
+            imdb_id: None,
+            vote_average: None,
+            genres: Vec::new(),
             cut: None,
+            release: None,
             id: None,
             path: None,
             original_runtime: None,
-            _marker: std::marker::PhantomData::default(),
+            _marker: std::marker::PhantomData,
         }
     }
 
@@ -57,17 +111,31 @@ impl Movie<Incomplete> {
         self
     }
 
+    pub fn runtime(&self) -> Option<u32> {
+        self.original_runtime
+    }
+
     pub fn complete(self, path: PathBuf) -> Movie<Complete> {
         // change after https://github.com/rust-lang/rust/issues/86555 stabilises
         Movie {
             path: Some(path),
+            provider: self.provider,
+            provider_id: self.provider_id,
             tmdb_id: self.tmdb_id,
             title: self.title,
             release_year: self.release_year,
+            original_title: self.original_title,
+            original_language: self.original_language,
+            overview: self.overview,
+            tagline: self.tagline,
+            imdb_id: self.imdb_id,
+            vote_average: self.vote_average,
+            genres: self.genres,
             original_runtime: self.original_runtime,
             cut: self.cut,
+            release: self.release,
             id: None,
-            _marker: std::marker::PhantomData::default(),
+            _marker: std::marker::PhantomData,
         }
     }
 }
@@ -77,8 +145,9 @@ impl Movie<Complete> {
         self.path.as_ref().unwrap()
     }
 
-    pub fn original_runtime(&self) -> &u32 {
-        self.original_runtime.as_ref().unwrap()
+    /// Runtime in minutes, `None` when provider does not know it
+    pub fn original_runtime(&self) -> Option<u32> {
+        self.original_runtime
     }
 
     pub fn cut(&self) -> &Option<String> {
@@ -88,6 +157,15 @@ impl Movie<Complete> {
     pub fn cut_mut(&mut self) -> &mut Option<String> {
         &mut self.cut
     }
+
+    /// Release info of file, like `1080p BluRay H.264`
+    pub fn release(&self) -> &Option<ReleaseInfo> {
+        &self.release
+    }
+
+    pub fn release_mut(&mut self) -> &mut Option<ReleaseInfo> {
+        &mut self.release
+    }
 }
 
 impl Movie<Loaded> {
@@ -99,13 +177,43 @@ impl Movie<Loaded> {
         self.path.as_ref().unwrap()
     }
 
-    pub fn original_runtime(&self) -> &u32 {
-        self.original_runtime.as_ref().unwrap()
+    pub fn path_mut(&mut self) -> &mut PathBuf {
+        self.path.as_mut().unwrap()
+    }
+
+    /// Runtime in minutes, `None` when provider does not know it
+    pub fn original_runtime(&self) -> Option<u32> {
+        self.original_runtime
     }
 
     pub fn cut(&self) -> &Option<String> {
         &self.cut
     }
+
+    pub fn cut_mut(&mut self) -> &mut Option<String> {
+        &mut self.cut
+    }
+
+    pub fn release(&self) -> &Option<ReleaseInfo> {
+        &self.release
+    }
+
+    /// Replaces TMDB provided data with new details, keeping local data like path and cut.
+    pub fn apply_detail(&mut self, detail: IncompleteMovie) {
+        self.provider = detail.provider;
+        self.provider_id = detail.provider_id;
+        self.tmdb_id = detail.tmdb_id;
+        self.title = detail.title;
+        self.release_year = detail.release_year;
+        self.original_title = detail.original_title;
+        self.original_language = detail.original_language;
+        self.overview = detail.overview;
+        self.tagline = detail.tagline;
+        self.imdb_id = detail.imdb_id;
+        self.vote_average = detail.vote_average;
+        self.genres = detail.genres;
+        self.original_runtime = detail.original_runtime;
+    }
 }
 
 impl<T: EntityState> Creatable<Movie<T>> for Database {
@@ -117,7 +225,15 @@ impl<T: EntityState> Creatable<Movie<T>> for Database {
             `cut` TEXT,
             `path` TEXT,
             `original_runtime` INTEGER,
-            `release_year` INTEGER
+            `release_year` INTEGER,
+            `original_title` TEXT,
+            `original_language` TEXT,
+            `overview` TEXT,
+            `tagline` TEXT,
+            `imdb_id` TEXT,
+            `vote_average` REAL,
+            `provider` TEXT NOT NULL DEFAULT 'tmdb',
+            `provider_id` TEXT NOT NULL DEFAULT ''
         );"
     }
 }
@@ -125,17 +241,29 @@ impl<T: EntityState> Creatable<Movie<T>> for Database {
 impl Insertable<CompleteMovie> for Database {
     fn insert(&self, object: CompleteMovie) -> Result<usize, Error> {
         let Movie {
+            provider,
+            provider_id,
             tmdb_id,
             title,
             cut,
             path,
             original_runtime,
             release_year,
+            release,
+            original_title,
+            original_language,
+            overview,
+            tagline,
+            imdb_id,
+            vote_average,
+            genres,
             ..
         } = object;
 
+        let tx = self.conn.unchecked_transaction()?;
+
         let mut stmt = self.conn.prepare(
-            "INSERT INTO `movie` (tmdb_id, title, cut, path, original_runtime, release_year) VALUES (?, ?, ?, ?, ?, ?)",
+            "INSERT INTO `movie` (tmdb_id, title, cut, path, original_runtime, release_year, original_title, original_language, overview, tagline, imdb_id, vote_average, provider, provider_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )?;
 
         stmt.execute(params![
@@ -144,10 +272,23 @@ impl Insertable<CompleteMovie> for Database {
             cut,
             path.unwrap().to_string_lossy(),
             original_runtime,
-            release_year
+            release_year,
+            original_title,
+            original_language,
+            overview,
+            tagline,
+            imdb_id,
+            vote_average,
+            provider,
+            provider_id
         ])?;
 
-        Database::last_insert_id(self)
+        let id = Database::last_insert_id(self)?;
+        self.set_movie_genres(id, &genres)?;
+        self.set_movie_release(id, release.as_ref())?;
+        tx.commit()?;
+
+        Ok(id)
     }
 }
 
@@ -155,7 +296,9 @@ impl Selectable<LoadedMovie> for Database {
     fn select_by_id(&self, id: usize) -> Result<Option<LoadedMovie>, Error> {
         let mut stmt = self.conn.prepare("SELECT * FROM `movie` WHERE `id` = ?")?;
 
-        Ok(stmt.query_row([id], movie_mapper).optional()?)
+        let movie = stmt.query_row([id], movie_mapper).optional()?;
+
+        movie.map(|m| self.with_details(m)).transpose()
     }
 
     fn list_all(&self) -> Result<Vec<LoadedMovie>, Error> {
@@ -165,13 +308,130 @@ impl Selectable<LoadedMovie> for Database {
 
         let mut vec = Vec::new();
         for row in mapped {
-            vec.push(row?);
+            vec.push(self.with_details(row?)?);
         }
 
         Ok(vec)
     }
 }
 
+impl Updatable<LoadedMovie> for Database {
+    fn update(&self, object: &LoadedMovie) -> Result<(), Error> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut stmt = self.conn.prepare(
+            "UPDATE `movie` SET tmdb_id = ?, title = ?, cut = ?, path = ?, original_runtime = ?, release_year = ?, original_title = ?, original_language = ?, overview = ?, tagline = ?, imdb_id = ?, vote_average = ?, provider = ?, provider_id = ? WHERE `id` = ?",
+        )?;
+
+        let updated = stmt.execute(params![
+            object.tmdb_id,
+            object.title.as_str(),
+            object.cut,
+            object.path().to_string_lossy(),
+            object.original_runtime,
+            object.release_year,
+            object.original_title,
+            object.original_language,
+            object.overview,
+            object.tagline,
+            object.imdb_id,
+            object.vote_average,
+            object.provider,
+            object.provider_id,
+            object.id()
+        ])?;
+        if updated == 0 {
+            return Err(DbError::NotFound {
+                table: "movie",
+                id: *object.id(),
+            }
+            .into());
+        }
+
+        self.set_movie_genres(*object.id(), &object.genres)?;
+        self.set_movie_release(*object.id(), object.release.as_ref())?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+impl Deletable<LoadedMovie> for Database {
+    fn delete(&self, object: LoadedMovie) -> Result<(), Error> {
+        let mut stmt = self.conn.prepare("DELETE FROM `movie` WHERE `id` = ?")?;
+
+        if stmt.execute([object.id()])? == 0 {
+            return Err(DbError::NotFound {
+                table: "movie",
+                id: *object.id(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Finds movie stored with given file path
+    pub fn select_movie_by_path(&self, path: &Path) -> Result<Option<LoadedMovie>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM `movie` WHERE `path` = ?")?;
+
+        let movie = stmt
+            .query_row([path.to_string_lossy()], movie_mapper)
+            .optional()?;
+
+        movie.map(|m| self.with_details(m)).transpose()
+    }
+
+    /// Finds movies with cast or crew member, whose name contains `name`, ignoring case
+    pub fn select_movies_by_person_name(&self, name: &str) -> Result<Vec<LoadedMovie>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM `movie` WHERE `id` IN (
+                SELECT `movie_credit`.`movie_id` FROM `movie_credit`
+                JOIN `person` ON `person`.`id` = `movie_credit`.`person_id`
+                WHERE `person`.`name` LIKE ? ESCAPE '\\'
+            )",
+        )?;
+
+        let mapped = stmt.query_map([like_pattern(name)], movie_mapper)?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(self.with_details(row?)?);
+        }
+
+        Ok(vec)
+    }
+
+    /// Finds movies person had any part in, ordered by release year
+    pub fn select_movies_by_person(&self, person_id: usize) -> Result<Vec<LoadedMovie>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM `movie` WHERE `id` IN (
+                SELECT `movie_id` FROM `movie_credit` WHERE `person_id` = ?
+            ) ORDER BY `release_year`, `title`",
+        )?;
+
+        let mapped = stmt.query_map([person_id], movie_mapper)?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(self.with_details(row?)?);
+        }
+
+        Ok(vec)
+    }
+
+    fn with_details(&self, mut movie: LoadedMovie) -> Result<LoadedMovie, Error> {
+        movie.genres = self.select_movie_genres(*movie.id())?;
+        movie.release = self.select_movie_release(*movie.id())?;
+
+        Ok(movie)
+    }
+}
+
 fn movie_mapper(row: &Row) -> Result<LoadedMovie, rusqlite::Error> {
     Ok(Movie {
         id: row.get(0)?,
@@ -181,6 +441,225 @@ fn movie_mapper(row: &Row) -> Result<LoadedMovie, rusqlite::Error> {
         path: Some(PathBuf::from(row.get::<usize, String>(4)?)),
         original_runtime: row.get(5)?,
         release_year: row.get(6)?,
-        _marker: std::marker::PhantomData::default(),
+        release: None,
+        original_title: row.get(7)?,
+        original_language: row.get(8)?,
+        overview: row.get(9)?,
+        tagline: row.get(10)?,
+        imdb_id: row.get(11)?,
+        vote_average: row.get(12)?,
+        provider: row.get(13)?,
+        provider_id: row.get(14)?,
+        genres: Vec::new(),
+        _marker: std::marker::PhantomData,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_movie(db: &Database) -> usize {
+        let mut movie = IncompleteMovie::new(603, "The Matrix".into(), 1999);
+        movie.set_runtime(Some(136));
+
+        db.insert(movie.complete(PathBuf::from("/movies/matrix.mkv")))
+            .unwrap()
+    }
+
+    #[test]
+    fn it_stores_movie_of_other_provider() {
+        let db = Database::open_in_memory().unwrap();
+        let movie =
+            IncompleteMovie::with_provider("omdb", "tt0133093".into(), "The Matrix".into(), 1999);
+        let id = db
+            .insert(movie.complete(PathBuf::from("/movies/matrix.mkv")))
+            .unwrap();
+
+        let loaded: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(loaded.provider, "omdb");
+        assert_eq!(loaded.provider_id, "tt0133093");
+        assert_eq!(loaded.tmdb_id, None);
+    }
+
+    #[test]
+    fn it_loads_movie_without_runtime() {
+        let db = Database::open_in_memory().unwrap();
+        let movie = IncompleteMovie::new(603, "The Matrix".into(), 1999);
+        let id = db
+            .insert(movie.complete(PathBuf::from("/movies/matrix.mkv")))
+            .unwrap();
+
+        let loaded: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(loaded.original_runtime(), None);
+    }
+
+    #[test]
+    fn it_updates_movie() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_movie(&db);
+
+        let mut movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        movie.title = "The Matrix Reloaded".into();
+        movie.tmdb_id = Some(604);
+        movie.provider_id = "604".into();
+        *movie.cut_mut() = Some("Director's Cut".into());
+        *movie.path_mut() = PathBuf::from("/movies/matrix_reloaded.mkv");
+        db.update(&movie).unwrap();
+
+        let updated: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(updated.id(), &id);
+        assert_eq!(updated.tmdb_id, Some(604));
+        assert_eq!(updated.provider_id, "604");
+        assert_eq!(updated.title, "The Matrix Reloaded");
+        assert_eq!(updated.release_year, 1999);
+        assert_eq!(updated.original_runtime(), Some(136));
+        assert_eq!(updated.cut(), &Some("Director's Cut".into()));
+        assert_eq!(
+            updated.path(),
+            &PathBuf::from("/movies/matrix_reloaded.mkv")
+        );
+    }
+
+    #[test]
+    fn it_stores_details_and_genres() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut movie = IncompleteMovie::new(603, "The Matrix".into(), 1999);
+        movie.set_runtime(Some(136));
+        movie.tagline = Some("Welcome to the Real World.".into());
+        movie.imdb_id = Some("tt0133093".into());
+        movie.vote_average = Some(8.2);
+        movie.genres = vec![
+            Genre {
+                tmdb_id: 878,
+                name: "Science Fiction".into(),
+            },
+            Genre {
+                tmdb_id: 28,
+                name: "Action".into(),
+            },
+        ];
+        let id = db
+            .insert(movie.complete(PathBuf::from("/movies/matrix.mkv")))
+            .unwrap();
+        let other_id = insert_movie(&db);
+
+        let mut movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(movie.tagline, Some("Welcome to the Real World.".into()));
+        assert_eq!(movie.imdb_id, Some("tt0133093".into()));
+        assert_eq!(movie.vote_average, Some(8.2));
+        assert_eq!(movie.genres.len(), 2);
+        assert_eq!(movie.genres[0].name, "Action");
+
+        movie.genres.pop();
+        db.update(&movie).unwrap();
+
+        let movies: Vec<LoadedMovie> = db.list_all().unwrap();
+        assert_eq!(
+            movies[0].genres,
+            vec![Genre {
+                tmdb_id: 28,
+                name: "Action".into()
+            }]
+        );
+        assert!(movies[1].genres.is_empty());
+
+        let other: LoadedMovie = db.select_by_id(other_id).unwrap().unwrap();
+        db.delete(other).unwrap();
+        let movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        db.delete(movie).unwrap();
+        let links: usize = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM `movie_genre`", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(links, 0);
+    }
+
+    #[test]
+    fn it_selects_movie_by_path() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_movie(&db);
+
+        let movie = db
+            .select_movie_by_path(Path::new("/movies/matrix.mkv"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(movie.id(), &id);
+
+        let missing = db
+            .select_movie_by_path(Path::new("/movies/other.mkv"))
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn it_deletes_movie() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_movie(&db);
+        let other_id = insert_movie(&db);
+
+        let movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        db.delete(movie).unwrap();
+
+        let deleted: Option<LoadedMovie> = db.select_by_id(id).unwrap();
+        assert!(deleted.is_none());
+
+        let remaining: Vec<LoadedMovie> = db.list_all().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id(), &other_id);
+    }
+
+    #[test]
+    fn it_refuses_missing_movie() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_movie(&db);
+
+        let movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        let stale: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        db.delete(movie).unwrap();
+
+        assert!(matches!(
+            db.update(&stale),
+            Err(Error::Db(DbError::NotFound { table: "movie", .. }))
+        ));
+        assert!(matches!(
+            db.delete(stale),
+            Err(Error::Db(DbError::NotFound { table: "movie", .. }))
+        ));
+    }
+
+    #[test]
+    fn it_stores_release_info() {
+        let db = Database::open_in_memory().unwrap();
+
+        let release = ReleaseInfo {
+            resolution: Some("2160p".into()),
+            source: Some("BluRay".into()),
+            remux: true,
+            hdr: vec!["HDR10".into(), "Dolby Vision".into()],
+            group: Some("GROUP".into()),
+            ..Default::default()
+        };
+        let mut movie =
+            IncompleteMovie::new(603, "The Matrix".into(), 1999).complete("/matrix.mkv".into());
+        *movie.release_mut() = Some(release);
+        let id = db.insert(movie).unwrap();
+
+        let mut loaded: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        let release = loaded.release().as_ref().unwrap();
+        assert_eq!(release.resolution.as_deref(), Some("2160p"));
+        assert!(release.remux);
+        assert_eq!(release.hdr, vec!["HDR10", "Dolby Vision"]);
+        assert!(release.audio.is_empty());
+        assert_eq!(
+            release.to_string(),
+            "2160p BluRay Remux HDR10 Dolby Vision-GROUP"
+        );
+
+        loaded.release = None;
+        db.update(&loaded).unwrap();
+        let updated: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        assert!(updated.release().is_none());
+    }
+}
@@ -0,0 +1,300 @@
+use crate::db::{Creatable, Database, Selectable};
+use crate::error::Error;
+use crate::{EntityState, Incomplete, Loaded};
+use rusqlite::{params, OptionalExtension, Row};
+use std::fmt::{Debug, Formatter};
+
+/// Cast or crew member, identified by TMDB id
+pub struct Person<T: EntityState> {
+    pub tmdb_id: usize,
+    pub name: String,
+    // only on loaded
+    id: Option<usize>,
+
+    _marker: std::marker::PhantomData<T>,
+}
+
+pub type IncompletePerson = Person<Incomplete>;
+pub type LoadedPerson = Person<Loaded>;
+
+impl Person<Incomplete> {
+    pub fn new(tmdb_id: usize, name: String) -> Self {
+        Self {
+            tmdb_id,
+            name,
+            id: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl Person<Loaded> {
+    pub fn id(&self) -> &usize {
+        self.id.as_ref().unwrap()
+    }
+}
+
+impl<T: EntityState> Debug for Person<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Person")
+            .field("id", &self.id)
+            .field("tmdb_id", &self.tmdb_id)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Part person had in movie
+#[derive(Debug)]
+pub struct Credit<T: EntityState> {
+    pub person: Person<T>,
+    /// `Actor` for cast, job like `Director` for crew
+    pub role: String,
+    /// Played character, only for cast
+    pub character: Option<String>,
+    /// Billing order, only for cast
+    pub order: Option<u32>,
+}
+
+pub type IncompleteCredit = Credit<Incomplete>;
+pub type LoadedCredit = Credit<Loaded>;
+
+/// Role of all cast members
+pub const ACTOR: &str = "Actor";
+
+impl<T: EntityState> Credit<T> {
+    pub fn is_cast(&self) -> bool {
+        self.role == ACTOR
+    }
+}
+
+impl<T: EntityState> Creatable<Person<T>> for Database {
+    fn create_table_sql() -> &'static str {
+        "CREATE TABLE IF NOT EXISTS `person` (
+            `id` INTEGER PRIMARY KEY,
+            `tmdb_id` INTEGER NOT NULL UNIQUE,
+            `name` TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS `movie_credit` (
+            `movie_id` INTEGER NOT NULL REFERENCES `movie` (`id`) ON DELETE CASCADE,
+            `person_id` INTEGER NOT NULL REFERENCES `person` (`id`) ON DELETE CASCADE,
+            `role` TEXT NOT NULL,
+            `character` TEXT,
+            `order` INTEGER,
+            PRIMARY KEY (`movie_id`, `person_id`, `role`)
+        );"
+    }
+}
+
+impl Selectable<LoadedPerson> for Database {
+    fn select_by_id(&self, id: usize) -> Result<Option<LoadedPerson>, Error> {
+        let mut stmt = self.conn.prepare("SELECT * FROM `person` WHERE `id` = ?")?;
+
+        Ok(stmt.query_row([id], person_mapper).optional()?)
+    }
+
+    fn list_all(&self) -> Result<Vec<LoadedPerson>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM `person` ORDER BY `name`")?;
+
+        let mapped = stmt.query_map([], person_mapper)?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+
+        Ok(vec)
+    }
+}
+
+impl Database {
+    /// Finds people whose name contains `name`, ignoring case
+    pub fn search_people(&self, name: &str) -> Result<Vec<LoadedPerson>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM `person` WHERE `name` LIKE ? ESCAPE '\\' ORDER BY `name`")?;
+
+        let mapped = stmt.query_map([like_pattern(name)], person_mapper)?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+
+        Ok(vec)
+    }
+
+    /// Replaces cast and crew of movie, people are created when they are not stored yet
+    pub fn set_movie_credits(
+        &self,
+        movie_id: usize,
+        credits: &[IncompleteCredit],
+    ) -> Result<(), Error> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        self.conn.execute(
+            "DELETE FROM `movie_credit` WHERE `movie_id` = ?",
+            [movie_id],
+        )?;
+
+        let mut upsert = self.conn.prepare(
+            "INSERT INTO `person` (tmdb_id, name) VALUES (?, ?) ON CONFLICT (tmdb_id) DO UPDATE SET name = excluded.name",
+        )?;
+        // actor playing more characters is listed once per character, characters are joined
+        let mut link = self.conn.prepare(
+            "INSERT INTO `movie_credit` (movie_id, person_id, role, character, `order`) SELECT ?, `id`, ?, ?, ? FROM `person` WHERE `tmdb_id` = ?
+            ON CONFLICT (movie_id, person_id, role) DO UPDATE SET
+                character = CASE
+                    WHEN character IS NULL THEN excluded.character
+                    WHEN excluded.character IS NULL OR excluded.character = character THEN character
+                    ELSE character || ' / ' || excluded.character
+                END,
+                `order` = coalesce(min(`order`, excluded.`order`), `order`, excluded.`order`)",
+        )?;
+
+        for credit in credits {
+            upsert.execute(params![credit.person.tmdb_id, credit.person.name])?;
+            link.execute(params![
+                movie_id,
+                credit.role,
+                credit.character,
+                credit.order,
+                credit.person.tmdb_id
+            ])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Returns cast in billing order followed by crew
+    pub fn select_movie_credits(&self, movie_id: usize) -> Result<Vec<LoadedCredit>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT `person`.*, `movie_credit`.`role`, `movie_credit`.`character`, `movie_credit`.`order`
+            FROM `person`
+            JOIN `movie_credit` ON `movie_credit`.`person_id` = `person`.`id`
+            WHERE `movie_credit`.`movie_id` = ?
+            ORDER BY `movie_credit`.`order` IS NULL, `movie_credit`.`order`, `movie_credit`.`role`, `person`.`name`",
+        )?;
+
+        let mapped = stmt.query_map([movie_id], |row| {
+            Ok(Credit {
+                person: person_mapper(row)?,
+                role: row.get(3)?,
+                character: row.get(4)?,
+                order: row.get(5)?,
+            })
+        })?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+
+        Ok(vec)
+    }
+}
+
+/// Pattern for `LIKE` matching `value` anywhere, wildcards in `value` are matched literally
+pub(crate) fn like_pattern(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{escaped}%")
+}
+
+fn person_mapper(row: &Row) -> Result<LoadedPerson, rusqlite::Error> {
+    Ok(Person {
+        id: row.get(0)?,
+        tmdb_id: row.get(1)?,
+        name: row.get(2)?,
+        _marker: std::marker::PhantomData,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::movie::{IncompleteMovie, LoadedMovie};
+    use crate::db::{Deletable, Insertable};
+    use std::path::PathBuf;
+
+    fn credit(tmdb_id: usize, name: &str, role: &str, order: Option<u32>) -> IncompleteCredit {
+        Credit {
+            person: IncompletePerson::new(tmdb_id, name.into()),
+            role: role.into(),
+            character: order.map(|_| "Neo".into()),
+            order,
+        }
+    }
+
+    #[test]
+    fn it_stores_credits() {
+        let db = Database::open_in_memory().unwrap();
+        let movie = IncompleteMovie::new(603, "The Matrix".into(), 1999);
+        let id = db
+            .insert(movie.complete(PathBuf::from("/movies/matrix.mkv")))
+            .unwrap();
+
+        let credits = vec![
+            credit(9339, "Lilly Wachowski", "Director", None),
+            credit(9340, "Lana Wachowski", "Director", None),
+            credit(9340, "Lana Wachowski", "Writer", None),
+            credit(6384, "Keanu Reeves", ACTOR, Some(0)),
+        ];
+        db.set_movie_credits(id, &credits).unwrap();
+
+        let stored = db.select_movie_credits(id).unwrap();
+        assert_eq!(stored.len(), 4);
+        assert!(stored[0].is_cast());
+        assert_eq!(stored[0].person.name, "Keanu Reeves");
+        assert_eq!(stored[0].character, Some("Neo".into()));
+        assert_eq!(stored[1].person.name, "Lana Wachowski");
+        assert_eq!(stored[1].role, "Director");
+
+        let people: Vec<LoadedPerson> = db.list_all().unwrap();
+        assert_eq!(people.len(), 3);
+        let person: LoadedPerson = db.select_by_id(*people[0].id()).unwrap().unwrap();
+        assert_eq!(person.name, "Keanu Reeves");
+
+        let found = db.search_people("wachowski").unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(db.search_people("%").unwrap().is_empty());
+
+        let movies = db.select_movies_by_person(*found[0].id()).unwrap();
+        assert_eq!(movies.len(), 1);
+        assert_eq!(movies[0].id(), &id);
+        let movies = db.select_movies_by_person_name("KEANU").unwrap();
+        assert_eq!(movies.len(), 1);
+        assert!(db.select_movies_by_person_name("Hugo").unwrap().is_empty());
+
+        let movie: LoadedMovie = db.select_by_id(id).unwrap().unwrap();
+        db.delete(movie).unwrap();
+        assert!(db.select_movie_credits(id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_joins_characters_of_one_actor() {
+        let db = Database::open_in_memory().unwrap();
+        let movie = IncompleteMovie::new(10, "Movie".into(), 2000);
+        let id = db
+            .insert(movie.complete(PathBuf::from("/movies/movie.mkv")))
+            .unwrap();
+
+        let mut first = credit(1, "Actor Name", ACTOR, Some(3));
+        first.character = Some("Twin One".into());
+        let mut second = credit(1, "Actor Name", ACTOR, Some(1));
+        second.character = Some("Twin Two".into());
+        db.set_movie_credits(id, &[first, second]).unwrap();
+
+        let stored = db.select_movie_credits(id).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].character, Some("Twin One / Twin Two".into()));
+        assert_eq!(stored[0].order, Some(1));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::db::{Creatable, Database};
+use crate::error::Error;
+use crate::media::ReleaseInfo;
+use rusqlite::{params, OptionalExtension};
+
+/// Separator of values in list columns, like audio formats
+const LIST_SEPARATOR: char = ',';
+
+impl Creatable<ReleaseInfo> for Database {
+    fn create_table_sql() -> &'static str {
+        "CREATE TABLE IF NOT EXISTS `movie_release` (
+            `movie_id` INTEGER PRIMARY KEY REFERENCES `movie` (`id`) ON DELETE CASCADE,
+            `resolution` TEXT,
+            `source` TEXT,
+            `remux` INTEGER NOT NULL,
+            `video_codec` TEXT,
+            `audio` TEXT NOT NULL,
+            `hdr` TEXT NOT NULL,
+            `release_group` TEXT,
+            `languages` TEXT NOT NULL
+        );"
+    }
+}
+
+impl Database {
+    /// Replaces release info of movie, empty release info is not stored
+    pub(crate) fn set_movie_release(
+        &self,
+        movie_id: usize,
+        release: Option<&ReleaseInfo>,
+    ) -> Result<(), Error> {
+        self.conn.execute(
+            "DELETE FROM `movie_release` WHERE `movie_id` = ?",
+            [movie_id],
+        )?;
+
+        if let Some(release) = release.filter(|r| !r.is_empty()) {
+            self.conn.execute(
+                "INSERT INTO `movie_release` (movie_id, resolution, source, remux, video_codec, audio, hdr, release_group, languages) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    movie_id,
+                    release.resolution,
+                    release.source,
+                    release.remux,
+                    release.video_codec,
+                    join_list(&release.audio),
+                    join_list(&release.hdr),
+                    release.group,
+                    join_list(&release.languages)
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn select_movie_release(
+        &self,
+        movie_id: usize,
+    ) -> Result<Option<ReleaseInfo>, Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT `resolution`, `source`, `remux`, `video_codec`, `audio`, `hdr`, `release_group`, `languages` FROM `movie_release` WHERE `movie_id` = ?",
+        )?;
+
+        let release = stmt
+            .query_row([movie_id], |row| {
+                Ok(ReleaseInfo {
+                    resolution: row.get(0)?,
+                    source: row.get(1)?,
+                    remux: row.get(2)?,
+                    video_codec: row.get(3)?,
+                    audio: split_list(&row.get::<usize, String>(4)?),
+                    hdr: split_list(&row.get::<usize, String>(5)?),
+                    group: row.get(6)?,
+                    languages: split_list(&row.get::<usize, String>(7)?),
+                })
+            })
+            .optional()?;
+
+        Ok(release)
+    }
+}
+
+fn join_list(values: &[String]) -> String {
+    values.join(&LIST_SEPARATOR.to_string())
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(LIST_SEPARATOR)
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+        .collect()
+}
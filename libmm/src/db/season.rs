@@ -0,0 +1,186 @@
+use crate::db::{Creatable, Database, Insertable, Selectable};
+use crate::error::Error;
+use crate::{Complete, EntityState, Incomplete, Loaded};
+use rusqlite::{params, OptionalExtension, Row};
+use std::fmt::{Debug, Formatter};
+
+pub struct Season<T: EntityState> {
+    // are everywhere
+    pub tvmaze_id: usize,
+    pub number: u32,
+    // on loaded + complete
+    tvshow_id: Option<usize>,
+    // only on loaded
+    id: Option<usize>,
+
+    _marker: std::marker::PhantomData<T>,
+}
+
+pub type IncompleteSeason = Season<Incomplete>;
+pub type CompleteSeason = Season<Complete>;
+pub type LoadedSeason = Season<Loaded>;
+
+impl Season<Incomplete> {
+    pub fn new(tvmaze_id: usize, number: u32) -> Self {
+        Self {
+            tvmaze_id,
+            number,
+            tvshow_id: None,
+            id: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn complete(self, tvshow_id: usize) -> Season<Complete> {
+        // change after https://github.com/rust-lang/rust/issues/86555 stabilises
+        Season {
+            tvmaze_id: self.tvmaze_id,
+            number: self.number,
+            tvshow_id: Some(tvshow_id),
+            id: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl Season<Complete> {
+    pub fn tvshow_id(&self) -> &usize {
+        self.tvshow_id.as_ref().unwrap()
+    }
+}
+
+impl Season<Loaded> {
+    pub fn id(&self) -> &usize {
+        self.id.as_ref().unwrap()
+    }
+
+    pub fn tvshow_id(&self) -> &usize {
+        self.tvshow_id.as_ref().unwrap()
+    }
+}
+
+impl<T: EntityState> Debug for Season<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Season")
+            .field("id", &self.id)
+            .field("tvshow_id", &self.tvshow_id)
+            .field("tvmaze_id", &self.tvmaze_id)
+            .field("number", &self.number)
+            .finish()
+    }
+}
+
+impl<T: EntityState> Creatable<Season<T>> for Database {
+    fn create_table_sql() -> &'static str {
+        "CREATE TABLE IF NOT EXISTS `season` (
+            `id` INTEGER PRIMARY KEY,
+            `tvshow_id` INTEGER NOT NULL REFERENCES `tvshow` (`id`) ON DELETE CASCADE,
+            `tvmaze_id` INTEGER,
+            `number` INTEGER
+        );"
+    }
+}
+
+impl Insertable<CompleteSeason> for Database {
+    fn insert(&self, object: CompleteSeason) -> Result<usize, Error> {
+        let Season {
+            tvshow_id,
+            tvmaze_id,
+            number,
+            ..
+        } = object;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT INTO `season` (tvshow_id, tvmaze_id, number) VALUES (?, ?, ?)")?;
+
+        stmt.execute(params![tvshow_id, tvmaze_id, number])?;
+
+        Database::last_insert_id(self)
+    }
+}
+
+impl Selectable<LoadedSeason> for Database {
+    fn select_by_id(&self, id: usize) -> Result<Option<LoadedSeason>, Error> {
+        let mut stmt = self.conn.prepare("SELECT * FROM `season` WHERE `id` = ?")?;
+
+        Ok(stmt.query_row([id], season_mapper).optional()?)
+    }
+
+    fn list_all(&self) -> Result<Vec<LoadedSeason>, Error> {
+        let mut stmt = self.conn.prepare("SELECT * FROM `season`")?;
+
+        let mapped = stmt.query_map([], season_mapper)?;
+
+        let mut vec = Vec::new();
+        for row in mapped {
+            vec.push(row?);
+        }
+
+        Ok(vec)
+    }
+}
+
+impl Database {
+    /// Finds season of TV show by its number
+    pub fn select_season(
+        &self,
+        tvshow_id: usize,
+        number: u32,
+    ) -> Result<Option<LoadedSeason>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM `season` WHERE `tvshow_id` = ? AND `number` = ?")?;
+
+        Ok(stmt
+            .query_row(params![tvshow_id, number], season_mapper)
+            .optional()?)
+    }
+}
+
+fn season_mapper(row: &Row) -> Result<LoadedSeason, rusqlite::Error> {
+    Ok(Season {
+        id: row.get(0)?,
+        tvshow_id: row.get(1)?,
+        tvmaze_id: row.get(2)?,
+        number: row.get(3)?,
+        _marker: std::marker::PhantomData,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tvshow::IncompleteTvShow;
+
+    #[test]
+    fn it_requires_existing_tvshow() {
+        let db = Database::open_in_memory().unwrap();
+
+        let res = db.insert(IncompleteSeason::new(1, 1).complete(42));
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn it_selects_season() {
+        let db = Database::open_in_memory().unwrap();
+        let tvshow_id = db
+            .insert(IncompleteTvShow::new(82, "Game of Thrones".into()).complete())
+            .unwrap();
+
+        let id = db
+            .insert(IncompleteSeason::new(307, 1).complete(tvshow_id))
+            .unwrap();
+
+        let season: LoadedSeason = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(season.id(), &id);
+        assert_eq!(season.tvshow_id(), &tvshow_id);
+        assert_eq!(season.tvmaze_id, 307);
+        assert_eq!(season.number, 1);
+
+        let found = db.select_season(tvshow_id, 1).unwrap().unwrap();
+        assert_eq!(found.id(), &id);
+        assert!(db.select_season(tvshow_id, 2).unwrap().is_none());
+    }
+}
@@ -1,5 +1,5 @@
-use crate::db::{Creatable, Database, Insertable, Selectable};
-use crate::error::Error;
+use crate::db::{Creatable, Database, Deletable, Insertable, Selectable, Updatable};
+use crate::error::{DbError, Error};
 use crate::{Complete, EntityState, Incomplete, Loaded};
 use rusqlite::{params, OptionalExtension, Row};
 use std::fmt::{Debug, Formatter};
@@ -8,6 +8,10 @@ pub struct TvShow<T: EntityState> {
     // are everywhere
     pub tvmaze_id: usize,
     pub title: String,
+    pub premiered: Option<String>,
+    pub ended: Option<String>,
+    /// Id of the same show in TMDB
+    pub tmdb_id: Option<usize>,
     // only on loaded
     id: Option<usize>,
 
@@ -23,8 +27,11 @@ impl TvShow<Incomplete> {
         Self {
             tvmaze_id,
             title,
+            premiered: None,
+            ended: None,
+            tmdb_id: None,
             id: None,
-            _marker: std::marker::PhantomData::default(),
+            _marker: std::marker::PhantomData,
         }
     }
 
@@ -33,18 +40,30 @@ impl TvShow<Incomplete> {
         TvShow {
             tvmaze_id: self.tvmaze_id,
             title: self.title,
+            premiered: self.premiered,
+            ended: self.ended,
+            tmdb_id: self.tmdb_id,
             id: None,
-            _marker: std::marker::PhantomData::default(),
+            _marker: std::marker::PhantomData,
         }
     }
 }
 
+impl TvShow<Loaded> {
+    pub fn id(&self) -> &usize {
+        self.id.as_ref().unwrap()
+    }
+}
+
 impl<T: EntityState> Debug for TvShow<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TvShow")
             .field("id", &self.id)
             .field("tvmaze_id", &self.tvmaze_id)
             .field("title", &self.title)
+            .field("premiered", &self.premiered)
+            .field("ended", &self.ended)
+            .field("tmdb_id", &self.tmdb_id)
             .finish()
     }
 }
@@ -54,7 +73,10 @@ impl<T: EntityState> Creatable<TvShow<T>> for Database {
         "CREATE TABLE IF NOT EXISTS `tvshow` (
             `id` INTEGER PRIMARY KEY,
             `tvmaze_id` INTEGER,
-            `title` TEXT
+            `title` TEXT,
+            `premiered` TEXT,
+            `ended` TEXT,
+            `tmdb_id` INTEGER
         );"
     }
 }
@@ -62,14 +84,25 @@ impl<T: EntityState> Creatable<TvShow<T>> for Database {
 impl Insertable<TvShow<Complete>> for Database {
     fn insert(&self, object: TvShow<Complete>) -> Result<usize, Error> {
         let TvShow {
-            tvmaze_id, title, ..
+            tvmaze_id,
+            title,
+            premiered,
+            ended,
+            tmdb_id,
+            ..
         } = object;
 
-        let mut stmt = self
-            .conn
-            .prepare("INSERT INTO `tvshow` (tvmaze_id, title) VALUES (?, ?)")?;
+        let mut stmt = self.conn.prepare(
+            "INSERT INTO `tvshow` (tvmaze_id, title, premiered, ended, tmdb_id) VALUES (?, ?, ?, ?, ?)",
+        )?;
 
-        stmt.execute(params![tvmaze_id, title.as_str(),])?;
+        stmt.execute(params![
+            tvmaze_id,
+            title.as_str(),
+            premiered,
+            ended,
+            tmdb_id
+        ])?;
 
         Database::last_insert_id(self)
     }
@@ -96,11 +129,130 @@ impl Selectable<TvShow<Loaded>> for Database {
     }
 }
 
+impl Updatable<LoadedTvShow> for Database {
+    fn update(&self, object: &LoadedTvShow) -> Result<(), Error> {
+        let mut stmt = self.conn.prepare(
+            "UPDATE `tvshow` SET tvmaze_id = ?, title = ?, premiered = ?, ended = ?, tmdb_id = ? WHERE `id` = ?",
+        )?;
+
+        let updated = stmt.execute(params![
+            object.tvmaze_id,
+            object.title.as_str(),
+            object.premiered,
+            object.ended,
+            object.tmdb_id,
+            object.id()
+        ])?;
+        if updated == 0 {
+            return Err(DbError::NotFound {
+                table: "tvshow",
+                id: *object.id(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Deletable<LoadedTvShow> for Database {
+    fn delete(&self, object: LoadedTvShow) -> Result<(), Error> {
+        let mut stmt = self.conn.prepare("DELETE FROM `tvshow` WHERE `id` = ?")?;
+
+        if stmt.execute([object.id()])? == 0 {
+            return Err(DbError::NotFound {
+                table: "tvshow",
+                id: *object.id(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
 fn tvshow_mapper(row: &Row) -> Result<LoadedTvShow, rusqlite::Error> {
     Ok(TvShow {
         id: row.get(0)?,
         tvmaze_id: row.get(1)?,
         title: row.get(2)?,
-        _marker: std::marker::PhantomData::default(),
+        premiered: row.get(3)?,
+        ended: row.get(4)?,
+        tmdb_id: row.get(5)?,
+        _marker: std::marker::PhantomData,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_tvshow(db: &Database) -> usize {
+        let tvshow = IncompleteTvShow::new(82, "Game of Thrones".into());
+
+        db.insert(tvshow.complete()).unwrap()
+    }
+
+    #[test]
+    fn it_updates_tvshow() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_tvshow(&db);
+
+        let mut tvshow: LoadedTvShow = db.select_by_id(id).unwrap().unwrap();
+        tvshow.tvmaze_id = 83;
+        tvshow.title = "The Simpsons".into();
+        tvshow.premiered = Some("1989-12-17".into());
+        tvshow.tmdb_id = Some(456);
+        db.update(&tvshow).unwrap();
+
+        let updated: LoadedTvShow = db.select_by_id(id).unwrap().unwrap();
+        assert_eq!(updated.id(), &id);
+        assert_eq!(updated.tvmaze_id, 83);
+        assert_eq!(updated.title, "The Simpsons");
+        assert_eq!(updated.premiered, Some("1989-12-17".into()));
+        assert_eq!(updated.ended, None);
+        assert_eq!(updated.tmdb_id, Some(456));
+    }
+
+    #[test]
+    fn it_deletes_tvshow() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_tvshow(&db);
+        let other_id = insert_tvshow(&db);
+
+        let tvshow: LoadedTvShow = db.select_by_id(id).unwrap().unwrap();
+        db.delete(tvshow).unwrap();
+
+        let deleted: Option<LoadedTvShow> = db.select_by_id(id).unwrap();
+        assert!(deleted.is_none());
+
+        let remaining: Vec<LoadedTvShow> = db.list_all().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id(), &other_id);
+    }
+
+    #[test]
+    fn it_refuses_missing_tvshow() {
+        let db = Database::open_in_memory().unwrap();
+        let id = insert_tvshow(&db);
+
+        let tvshow: LoadedTvShow = db.select_by_id(id).unwrap().unwrap();
+        let stale: LoadedTvShow = db.select_by_id(id).unwrap().unwrap();
+        db.delete(tvshow).unwrap();
+
+        assert!(matches!(
+            db.update(&stale),
+            Err(Error::Db(DbError::NotFound {
+                table: "tvshow",
+                ..
+            }))
+        ));
+        assert!(matches!(
+            db.delete(stale),
+            Err(Error::Db(DbError::NotFound {
+                table: "tvshow",
+                ..
+            }))
+        ));
+    }
+}
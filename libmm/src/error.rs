@@ -4,7 +4,11 @@ use std::fmt::{Display, Formatter};
 pub enum ApiError {
     ApiKey,
     InvalidFormat,
-    Transport(ureq::Transport),
+    /// Response is not cached and no requests may be sent
+    Offline,
+    /// Too many requests were sent, even after waiting and retrying
+    RateLimited,
+    Transport(Box<ureq::Transport>),
     Unknown(String),
 }
 
@@ -13,6 +17,8 @@ impl Display for ApiError {
         match self {
             Self::ApiKey => f.write_str("Invalid API key"),
             Self::InvalidFormat => f.write_str("Invalid format of API response"),
+            Self::Offline => f.write_str("Response is not cached and offline mode is enabled"),
+            Self::RateLimited => f.write_str("Rate limit of API was exceeded, try again later"),
             Self::Transport(err) => f.write_fmt(format_args!("Transport Error: {err}")),
             Self::Unknown(msg) => f.write_str(msg),
         }
@@ -21,14 +27,14 @@ impl Display for ApiError {
 
 impl From<ureq::Transport> for ApiError {
     fn from(t: ureq::Transport) -> Self {
-        Self::Transport(t)
+        Self::Transport(Box::new(t))
     }
 }
 
 impl std::error::Error for ApiError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            ApiError::Transport(t) => Some(t),
+            ApiError::Transport(t) => Some(t.as_ref()),
             _ => None,
         }
     }
@@ -37,12 +43,27 @@ impl std::error::Error for ApiError {
 #[derive(Debug)]
 pub enum DbError {
     Sqlite(rusqlite::Error),
+    UnsupportedSchema {
+        found: u32,
+        supported: u32,
+    },
+    /// Updated or deleted row does not exist
+    NotFound {
+        table: &'static str,
+        id: usize,
+    },
 }
 
 impl Display for DbError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Sqlite(e) => f.write_fmt(format_args!("SQLite error: {e}")),
+            Self::UnsupportedSchema { found, supported } => f.write_fmt(format_args!(
+                "Database schema version {found} is newer than supported version {supported}"
+            )),
+            Self::NotFound { table, id } => {
+                f.write_fmt(format_args!("No row with id {id} in table `{table}`"))
+            }
         }
     }
 }
@@ -57,6 +78,7 @@ impl std::error::Error for DbError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             DbError::Sqlite(e) => Some(e),
+            _ => None,
         }
     }
 }
@@ -64,16 +86,23 @@ impl std::error::Error for DbError {
 #[derive(Debug)]
 pub enum MediaError {
     Matroska(matroska::MatroskaError),
+    Io(std::io::Error),
     NoVideoTrack,
     IncompleteMetadata,
+    /// Container format was not recognized from file header
+    UnsupportedContainer,
 }
 
 impl Display for MediaError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             MediaError::Matroska(e) => f.write_fmt(format_args!("Matroska error: {e}")),
+            MediaError::Io(e) => f.write_fmt(format_args!("Failed to read media file: {e}")),
             MediaError::NoVideoTrack => f.write_str("No video track was found"),
             MediaError::IncompleteMetadata => f.write_str("Cannot real all needed metadata"),
+            MediaError::UnsupportedContainer => f.write_str(
+                "Unsupported container format, only Matroska, WebM, MP4, AVI and MPEG-TS are supported",
+            ),
         }
     }
 }
@@ -88,6 +117,7 @@ impl std::error::Error for MediaError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             MediaError::Matroska(e) => Some(e),
+            MediaError::Io(e) => Some(e),
             _ => None,
         }
     }
@@ -1,5 +1,23 @@
+mod avi;
+mod ebml;
+mod matcher;
+mod matroska;
 mod metadata;
+mod mp4;
+mod mpegts;
 mod name_parser;
+mod probe;
+mod release;
 
-pub use metadata::MediaMetadata;
-pub use name_parser::{NameParser, ParsedName};
+pub use avi::AviProbe;
+pub use matcher::MovieMatcher;
+pub use matroska::MatroskaProbe;
+pub use metadata::{
+    Attachment, AttachmentKind, AudioTrack, Chapter, Colour, HdrFormat, MediaMetadata,
+    SubtitleTrack, VideoTrack,
+};
+pub use mp4::Mp4Probe;
+pub use mpegts::MpegTsProbe;
+pub use name_parser::{EpisodeNumber, NameParser, ParsedEpisode, ParsedName};
+pub use probe::MediaProbe;
+pub use release::ReleaseInfo;
@@ -0,0 +1,333 @@
+//! Reader of AVI files, including OpenDML files larger than 1 GB
+
+use crate::error::{Error, MediaError};
+use crate::media::metadata::{bitrate, AudioTrack, MediaMetadata, VideoTrack};
+use crate::media::MediaProbe;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// Larger header list is surely corrupted, it holds only a few short chunks
+const MAX_HEADER_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Audio format tag of `WAVEFORMATEXTENSIBLE`, real tag is in its sub format
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Probe of AVI files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AviProbe;
+
+impl MediaProbe for AviProbe {
+    fn name(&self) -> &'static str {
+        "AVI"
+    }
+
+    fn matches(&self, header: &[u8]) -> bool {
+        header.get(..4) == Some(b"RIFF") && header.get(8..12) == Some(b"AVI ")
+    }
+
+    fn probe(&self, file: &mut File) -> Result<MediaMetadata, Error> {
+        read(file)
+    }
+}
+
+fn read<R: Read + Seek>(reader: &mut R) -> Result<MediaMetadata, Error> {
+    let file_size = reader.seek(SeekFrom::End(0)).map_err(MediaError::Io)?;
+    let hdrl = read_header_list(reader).map_err(MediaError::Io)?;
+
+    let mut main_header = None;
+    let mut total_frames = None;
+    let mut video = None;
+    let mut audio_tracks = Vec::new();
+
+    for (id, body) in chunks(&hdrl) {
+        match (&id, list_type(&id, body)) {
+            (b"avih", _) => main_header = Some(body),
+            (_, Some(b"strl")) => match parse_stream(&body[4..]) {
+                Some(Stream::Video(v, duration)) if video.is_none() => video = Some((v, duration)),
+                Some(Stream::Audio(a)) => audio_tracks.push(a),
+                _ => {}
+            },
+            // extended header of OpenDML, frames of the main header are counted only in the first RIFF
+            (_, Some(b"odml")) => {
+                total_frames = find_chunk(&body[4..], b"dmlh").and_then(|dmlh| read_u32(dmlh, 0))
+            }
+            _ => {}
+        }
+    }
+
+    let (video, stream_duration) = video.ok_or(MediaError::NoVideoTrack)?;
+
+    let duration = stream_duration
+        .or_else(|| {
+            let main_header = main_header?;
+            let frame_duration = read_u32(main_header, 0)?;
+            let frames = total_frames.or_else(|| read_u32(main_header, 16))?;
+
+            Some(Duration::from_micros(
+                u64::from(frame_duration) * u64::from(frames),
+            ))
+        })
+        .filter(|d| !d.is_zero())
+        .ok_or(MediaError::IncompleteMetadata)?;
+
+    Ok(MediaMetadata {
+        duration,
+        file_size,
+        bitrate: bitrate(file_size, duration),
+        video,
+        audio_tracks,
+        subtitle_tracks: Vec::new(),
+        chapters: Vec::new(),
+        attachments: Vec::new(),
+    })
+}
+
+/// Reads body of `hdrl` list, which has to be the first chunk
+fn read_header_list<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(12))?;
+
+    let mut header = [0; 12];
+    reader.read_exact(&mut header)?;
+    let size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+
+    if &header[..4] != b"LIST" || &header[8..] != b"hdrl" || !(4..=MAX_HEADER_SIZE).contains(&size)
+    {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    let mut body = vec![0; size as usize - 4];
+    reader.read_exact(&mut body)?;
+
+    Ok(body)
+}
+
+enum Stream {
+    /// Video track with duration of stream, when it is known
+    Video(VideoTrack, Option<Duration>),
+    Audio(AudioTrack),
+}
+
+fn parse_stream(strl: &[u8]) -> Option<Stream> {
+    let strh = find_chunk(strl, b"strh")?;
+    let strf = find_chunk(strl, b"strf")?;
+    let name = find_chunk(strl, b"strn").and_then(read_string);
+
+    let scale = read_u32(strh, 20)?;
+    let rate = read_u32(strh, 24)?;
+
+    match strh.get(..4)? {
+        b"vids" => {
+            let frame_rate = (scale != 0 && rate != 0).then(|| f64::from(rate) / f64::from(scale));
+            let duration = frame_rate.map(|fps| {
+                Duration::from_secs_f64(f64::from(read_u32(strh, 32).unwrap_or_default()) / fps)
+            });
+            let compression: [u8; 4] = strf.get(16..20)?.try_into().ok()?;
+
+            let video = VideoTrack {
+                codec: video_codec(&compression),
+                width: u64::from(read_u32(strf, 4)?),
+                // negative height means top-down bitmap
+                height: u64::from((read_u32(strf, 8)? as i32).unsigned_abs()),
+                frame_rate,
+                bit_depth: None,
+                colour: None,
+                hdr: None,
+            };
+
+            Some(Stream::Video(video, duration.filter(|d| !d.is_zero())))
+        }
+        b"auds" => {
+            let tag = match read_u16(strf, 0)? {
+                WAVE_FORMAT_EXTENSIBLE => read_u16(strf, 24)?,
+                tag => tag,
+            };
+
+            Some(Stream::Audio(AudioTrack {
+                codec: audio_codec(tag),
+                name,
+                channels: u64::from(read_u16(strf, 2)?),
+                sample_rate: f64::from(read_u32(strf, 4)?),
+                language: None,
+                default: false,
+                forced: false,
+            }))
+        }
+        _ => None,
+    }
+}
+
+fn video_codec(compression: &[u8; 4]) -> String {
+    let fourcc = String::from_utf8_lossy(compression).to_uppercase();
+
+    let name = match fourcc.as_str() {
+        "XVID" | "DIVX" | "DX50" | "FMP4" | "MP4V" => "MPEG-4 Visual",
+        "H264" | "X264" | "AVC1" => "AVC / h264",
+        "HEVC" | "H265" | "HVC1" => "HEVC / h265",
+        "MJPG" => "MJPEG",
+        _ => return fourcc.trim_end_matches(['\0', ' ']).to_owned(),
+    };
+
+    name.into()
+}
+
+fn audio_codec(tag: u16) -> String {
+    let name = match tag {
+        0x0001 | 0x0003 => "PCM",
+        0x0050 => "MP2",
+        0x0055 => "MP3",
+        0x00FF | 0x1600 | 0x1610 | 0x706D => "AAC",
+        0x0161..=0x0163 => "WMA",
+        0x2000 => "AC-3",
+        0x2001 => "DTS",
+        0xF1AC => "FLAC",
+        0x674F..=0x6751 | 0x676F..=0x6771 => "Vorbis",
+        tag => return format!("0x{tag:04X}"),
+    };
+
+    name.into()
+}
+
+/// Type of `LIST` chunk
+fn list_type<'a>(id: &[u8; 4], body: &'a [u8]) -> Option<&'a [u8]> {
+    (id == b"LIST").then(|| body.get(..4)).flatten()
+}
+
+/// Iterates over chunks in `data`, returning their id and body
+fn chunks(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut rest = data;
+
+    std::iter::from_fn(move || {
+        let id: [u8; 4] = rest.get(..4)?.try_into().ok()?;
+        let size = read_u32(rest, 4)? as usize;
+        let body = rest.get(8..8 + size)?;
+
+        // chunks are aligned to 2 bytes
+        rest = rest.get(8 + size + size % 2..).unwrap_or_default();
+
+        Some((id, body))
+    })
+}
+
+fn find_chunk<'a>(data: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+    chunks(data).find(|(i, _)| i == id).map(|(_, body)| body)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// Null terminated string
+fn read_string(data: &[u8]) -> Option<String> {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+
+    Some(String::from_utf8_lossy(&data[..end]).into_owned()).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::metadata::tests::write_file;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = [id, &(body.len() as u32).to_le_bytes(), body].concat();
+        if body.len() % 2 == 1 {
+            bytes.push(0);
+        }
+
+        bytes
+    }
+
+    fn list(kind: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+        chunk(b"LIST", &[&kind[..], &children.concat()].concat())
+    }
+
+    fn le(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    fn stream_header(kind: &[u8; 4], scale: u32, rate: u32, length: u32) -> Vec<u8> {
+        chunk(
+            b"strh",
+            &[
+                &kind[..],
+                &[0; 16],
+                &le(&[scale, rate, 0, length]),
+                &[0; 20],
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn it_reads_avi_file() {
+        let avih = chunk(
+            b"avih",
+            &le(&[41_708, 0, 0, 0, 1000, 0, 2, 0, 720, 304, 0, 0, 0, 0]),
+        );
+
+        let bitmap = [&le(&[40, 720, 304])[..], &[1, 0, 24, 0], b"XVID", &[0; 20]].concat();
+        let video = list(
+            b"strl",
+            &[
+                stream_header(b"vids", 1001, 24_000, 172_656),
+                chunk(b"strf", &bitmap),
+            ],
+        );
+
+        let wave = [
+            &[0x00, 0x20, 0x06, 0x00][..],
+            &le(&[48_000, 56_000]),
+            &[0; 6],
+        ]
+        .concat();
+        let audio = list(
+            b"strl",
+            &[
+                stream_header(b"auds", 1, 48_000, 0),
+                chunk(b"strf", &wave),
+                chunk(b"strn", b"Commentary\0"),
+            ],
+        );
+
+        let hdrl = list(
+            b"hdrl",
+            &[
+                avih,
+                video,
+                audio,
+                list(b"odml", &[chunk(b"dmlh", &le(&[172_656]))]),
+            ],
+        );
+        let movi = list(b"movi", &[chunk(b"00dc", &[0; 1001])]);
+        let body = [&b"AVI "[..], &hdrl, &movi].concat();
+        let bytes = chunk(b"RIFF", &body);
+        let path = write_file("movie.avi", &bytes);
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.duration.as_secs(), 7201);
+        assert_eq!(metadata.file_size, bytes.len() as u64);
+
+        let video = &metadata.video;
+        assert_eq!(video.codec, "MPEG-4 Visual");
+        assert_eq!((video.width, video.height), (720, 304));
+        assert_eq!(format!("{:.3}", video.frame_rate.unwrap()), "23.976");
+
+        assert_eq!(metadata.audio_tracks.len(), 1);
+        let audio = &metadata.audio_tracks[0];
+        assert_eq!(audio.codec, "AC-3");
+        assert_eq!(audio.layout(), "5.1");
+        assert_eq!(audio.sample_rate, 48_000.0);
+        assert_eq!(audio.name.as_deref(), Some("Commentary"));
+    }
+}
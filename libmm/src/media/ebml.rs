@@ -0,0 +1,211 @@
+//! Minimal reader of Matroska elements, which are not parsed by the `matroska` crate
+
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+
+const SEGMENT: u32 = 0x18538067;
+const CLUSTER: u32 = 0x1F43B675;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const VIDEO: u32 = 0xE0;
+const COLOUR: u32 = 0x55B0;
+const MATRIX_COEFFICIENTS: u32 = 0x55B1;
+const BITS_PER_CHANNEL: u32 = 0x55B2;
+const TRANSFER_CHARACTERISTICS: u32 = 0x55BA;
+const PRIMARIES: u32 = 0x55BB;
+const MAX_CLL: u32 = 0x55BC;
+const MAX_FALL: u32 = 0x55BD;
+const MASTERING_METADATA: u32 = 0x55D0;
+const BLOCK_ADDITION_MAPPING: u32 = 0x41E4;
+const BLOCK_ADD_ID_TYPE: u32 = 0x41E7;
+
+/// Colour settings of video track, values are codes from ITU-T H.273
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Colour {
+    pub matrix_coefficients: Option<u64>,
+    pub bits_per_channel: Option<u64>,
+    pub transfer_characteristics: Option<u64>,
+    pub primaries: Option<u64>,
+    /// Maximum content light level in cd/m²
+    pub max_cll: Option<u64>,
+    /// Maximum frame-average light level in cd/m²
+    pub max_fall: Option<u64>,
+    /// Whether mastering display metadata of HDR video are present
+    pub mastering_metadata: bool,
+}
+
+/// Values of track, which are missing in [`matroska::Track`]
+#[derive(Debug, Default)]
+pub(crate) struct TrackExtras {
+    pub colour: Option<Colour>,
+    /// Types of additional data stored in blocks, like Dolby Vision configuration
+    pub block_addition_types: Vec<u64>,
+}
+
+/// Reads extras of all tracks by their number, file is read from the start
+pub(crate) fn read_track_extras<R: Read + Seek>(
+    reader: &mut R,
+) -> io::Result<HashMap<u64, TrackExtras>> {
+    reader.seek(SeekFrom::Start(0))?;
+
+    // EBML header
+    let (_, size) = read_header(reader)?;
+    skip(reader, size)?;
+
+    let (id, _) = read_header(reader)?;
+    if id != SEGMENT {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    // tracks have to be stored before the first cluster
+    loop {
+        let (id, size) = read_header(reader)?;
+
+        match id {
+            TRACKS => return read_tracks(reader, size.ok_or(io::ErrorKind::InvalidData)?),
+            CLUSTER => return Ok(HashMap::new()),
+            _ => skip(reader, size)?,
+        }
+    }
+}
+
+fn read_tracks<R: Read + Seek>(reader: &mut R, size: u64) -> io::Result<HashMap<u64, TrackExtras>> {
+    let mut tracks = HashMap::new();
+
+    for_children(reader, size, |reader, id, size| {
+        if id != TRACK_ENTRY {
+            return skip(reader, Some(size));
+        }
+
+        let mut number = None;
+        let mut extras = TrackExtras::default();
+
+        for_children(reader, size, |reader, id, size| match id {
+            TRACK_NUMBER => {
+                number = Some(read_uint(reader, size)?);
+                Ok(())
+            }
+            VIDEO => for_children(reader, size, |reader, id, size| {
+                if id == COLOUR {
+                    extras.colour = Some(read_colour(reader, size)?);
+                    Ok(())
+                } else {
+                    skip(reader, Some(size))
+                }
+            }),
+            BLOCK_ADDITION_MAPPING => for_children(reader, size, |reader, id, size| {
+                if id == BLOCK_ADD_ID_TYPE {
+                    extras.block_addition_types.push(read_uint(reader, size)?);
+                    Ok(())
+                } else {
+                    skip(reader, Some(size))
+                }
+            }),
+            _ => skip(reader, Some(size)),
+        })?;
+
+        if let Some(number) = number {
+            tracks.insert(number, extras);
+        }
+
+        Ok(())
+    })?;
+
+    Ok(tracks)
+}
+
+fn read_colour<R: Read + Seek>(reader: &mut R, size: u64) -> io::Result<Colour> {
+    let mut colour = Colour::default();
+
+    for_children(reader, size, |reader, id, size| {
+        let field = match id {
+            MATRIX_COEFFICIENTS => &mut colour.matrix_coefficients,
+            BITS_PER_CHANNEL => &mut colour.bits_per_channel,
+            TRANSFER_CHARACTERISTICS => &mut colour.transfer_characteristics,
+            PRIMARIES => &mut colour.primaries,
+            MAX_CLL => &mut colour.max_cll,
+            MAX_FALL => &mut colour.max_fall,
+            MASTERING_METADATA => {
+                colour.mastering_metadata = true;
+                return skip(reader, Some(size));
+            }
+            _ => return skip(reader, Some(size)),
+        };
+
+        *field = Some(read_uint(reader, size)?);
+        Ok(())
+    })?;
+
+    Ok(colour)
+}
+
+/// Calls `f` with id and size of each child element, `f` has to read or skip whole element
+fn for_children<R, F>(reader: &mut R, size: u64, mut f: F) -> io::Result<()>
+where
+    R: Read + Seek,
+    F: FnMut(&mut R, u32, u64) -> io::Result<()>,
+{
+    let end = reader.stream_position()? + size;
+
+    while reader.stream_position()? < end {
+        let (id, size) = read_header(reader)?;
+        f(reader, id, size.ok_or(io::ErrorKind::InvalidData)?)?;
+    }
+
+    Ok(())
+}
+
+/// Returns id and size of element, size is `None` when it is unknown
+fn read_header<R: Read>(reader: &mut R) -> io::Result<(u32, Option<u64>)> {
+    let (id, _) = read_vint(reader, 4)?;
+    let (size, len) = read_vint(reader, 8)?;
+
+    let marker = 1 << (7 * len);
+    let value = size & (marker - 1);
+    // all value bits set means unknown size
+    let size = (value != marker - 1).then_some(value);
+
+    Ok((id as u32, size))
+}
+
+/// Returns raw value with length marker and its length in bytes
+fn read_vint<R: Read>(reader: &mut R, max_len: u32) -> io::Result<(u64, u32)> {
+    let mut byte = [0; 1];
+    reader.read_exact(&mut byte)?;
+
+    let len = byte[0].leading_zeros() + 1;
+    if len > max_len {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    let mut value = u64::from(byte[0]);
+    for _ in 1..len {
+        reader.read_exact(&mut byte)?;
+        value = (value << 8) | u64::from(byte[0]);
+    }
+
+    Ok((value, len))
+}
+
+fn read_uint<R: Read>(reader: &mut R, size: u64) -> io::Result<u64> {
+    if size > 8 {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+
+    let mut value = 0;
+    let mut byte = [0; 1];
+    for _ in 0..size {
+        reader.read_exact(&mut byte)?;
+        value = (value << 8) | u64::from(byte[0]);
+    }
+
+    Ok(value)
+}
+
+fn skip<R: Seek>(reader: &mut R, size: Option<u64>) -> io::Result<()> {
+    let size = size.ok_or(io::ErrorKind::InvalidData)?;
+    reader.seek(SeekFrom::Current(size as i64))?;
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use crate::db::movie::IncompleteMovie;
+use crate::media::ParsedName;
+use std::time::Duration;
+use unicode_normalization::UnicodeNormalization;
+
+/// Minimal score of best candidate to be selected without asking
+const CONFIDENT_SCORE: f64 = 0.85;
+/// Minimal difference between best and second best candidate to be selected without asking
+const CONFIDENT_MARGIN: f64 = 0.1;
+
+const TITLE_WEIGHT: f64 = 0.6;
+const YEAR_WEIGHT: f64 = 0.3;
+const RUNTIME_WEIGHT: f64 = 0.1;
+
+const ARTICLES: &[&str] = &["the", "a", "an"];
+
+/// Scores search results against name parsed from filename
+pub struct MovieMatcher {
+    title: String,
+    year: Option<usize>,
+    duration: Option<Duration>,
+}
+
+impl MovieMatcher {
+    pub fn new(parsed: &ParsedName) -> Self {
+        Self {
+            title: normalize_title(&parsed.title),
+            year: parsed.year,
+            duration: None,
+        }
+    }
+
+    /// Duration of the file, compared with runtime of candidates which have it
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Runtime of candidates affects score only when duration of file is known
+    pub fn uses_runtime(&self) -> bool {
+        self.duration.is_some()
+    }
+
+    /// Returns score between 0 and 1, higher is better
+    pub fn score(&self, movie: &IncompleteMovie) -> f64 {
+        let mut score =
+            TITLE_WEIGHT * title_similarity(&self.title, &normalize_title(&movie.title));
+        let mut weights = TITLE_WEIGHT;
+
+        if let Some(year) = self.year {
+            let distance = (year as i64 - movie.release_year as i64).unsigned_abs();
+            let year_score = match distance {
+                0 => 1.0,
+                1 => 0.7,
+                2 => 0.3,
+                _ => 0.0,
+            };
+
+            score += YEAR_WEIGHT * year_score;
+            weights += YEAR_WEIGHT;
+        }
+
+        if let (Some(duration), Some(runtime)) = (self.duration, movie.runtime()) {
+            let minutes = duration.as_secs_f64() / 60.0;
+            let distance = (minutes - runtime as f64).abs();
+            // runtimes from TMDB are rounded, alternate cuts differ more
+            let runtime_score = 1.0 - ((distance - 2.0).max(0.0) / 15.0).min(1.0);
+
+            score += RUNTIME_WEIGHT * runtime_score;
+            weights += RUNTIME_WEIGHT;
+        }
+
+        score / weights
+    }
+
+    /// Returns indices of candidates with scores, best first
+    pub fn rank(&self, movies: &[IncompleteMovie]) -> Vec<(usize, f64)> {
+        let mut ranked = movies
+            .iter()
+            .map(|m| self.score(m))
+            .enumerate()
+            .collect::<Vec<_>>();
+
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        ranked
+    }
+
+    /// Returns index of best candidate, if it is clearly better than others
+    pub fn confident_match(&self, movies: &[IncompleteMovie]) -> Option<usize> {
+        let ranked = self.rank(movies);
+
+        match ranked.as_slice() {
+            [(index, best), rest @ ..] if *best >= CONFIDENT_SCORE => match rest.first() {
+                Some((_, second)) if best - second < CONFIDENT_MARGIN => None,
+                _ => Some(*index),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Lowercases title, removes accents, punctuation and leading article
+fn normalize_title(title: &str) -> String {
+    let title = title
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| match c {
+            '&' => " and ".into(),
+            c if c.is_alphanumeric() => c.to_lowercase().to_string(),
+            '\'' | '’' => String::new(),
+            _ => " ".into(),
+        })
+        .collect::<String>();
+
+    let mut words = title.split_whitespace().peekable();
+
+    if let Some(first) = words.peek() {
+        if ARTICLES.contains(first) {
+            words.next();
+        }
+    }
+
+    words.collect::<Vec<_>>().join(" ")
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}')
+}
+
+/// Similarity based on edit distance, 1 for equal titles
+fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    1.0 - levenshtein(&a, &b) as f64 / max_len as f64
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(title: &str, year: Option<usize>) -> ParsedName {
+        ParsedName {
+            title: title.into(),
+            year,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_normalizes_titles() {
+        assert_eq!(normalize_title("Amélie"), "amelie");
+        assert_eq!(
+            normalize_title("The Lord of the Rings"),
+            "lord of the rings"
+        );
+        assert_eq!(normalize_title("Tom & Jerry"), "tom and jerry");
+        assert_eq!(normalize_title("Schindler's List"), "schindlers list");
+        assert_eq!(
+            normalize_title("Léon: The Professional"),
+            "leon the professional"
+        );
+    }
+
+    #[test]
+    fn it_scores_candidates() {
+        let matcher = MovieMatcher::new(&parsed("Amelie", Some(2001)));
+
+        let exact = IncompleteMovie::new(194, "Amélie".into(), 2001);
+        let other_year = IncompleteMovie::new(1, "Amélie".into(), 2008);
+        let other_title = IncompleteMovie::new(2, "Emilie".into(), 2001);
+
+        assert_eq!(matcher.score(&exact), 1.0);
+        assert!(matcher.score(&exact) > matcher.score(&other_year));
+        assert!(matcher.score(&exact) > matcher.score(&other_title));
+    }
+
+    #[test]
+    fn it_uses_runtime() {
+        let matcher = MovieMatcher::new(&parsed("Dune", None))
+            .with_duration(Some(Duration::from_secs(155 * 60)));
+
+        let mut dune_2021 = IncompleteMovie::new(438631, "Dune".into(), 2021);
+        dune_2021.set_runtime(Some(155));
+        let mut dune_1984 = IncompleteMovie::new(841, "Dune".into(), 1984);
+        dune_1984.set_runtime(Some(137));
+
+        let movies = [dune_1984, dune_2021];
+
+        assert_eq!(matcher.confident_match(&movies), Some(1));
+    }
+
+    #[test]
+    fn it_selects_confident_match() {
+        let matcher = MovieMatcher::new(&parsed("The Matrix", Some(1999)));
+
+        let movies = [
+            IncompleteMovie::new(604, "The Matrix Reloaded".into(), 2003),
+            IncompleteMovie::new(603, "The Matrix".into(), 1999),
+            IncompleteMovie::new(605, "The Matrix Revolutions".into(), 2003),
+        ];
+
+        assert_eq!(matcher.confident_match(&movies), Some(1));
+        assert_eq!(matcher.rank(&movies)[0].0, 1);
+    }
+
+    #[test]
+    fn it_refuses_ambiguous_match() {
+        let matcher = MovieMatcher::new(&parsed("Dune", None));
+
+        let movies = [
+            IncompleteMovie::new(841, "Dune".into(), 1984),
+            IncompleteMovie::new(438631, "Dune".into(), 2021),
+        ];
+
+        assert_eq!(matcher.confident_match(&movies), None);
+        assert_eq!(matcher.confident_match(&[]), None);
+    }
+}
@@ -0,0 +1,246 @@
+//! Reader of Matroska and WebM files, which share the same EBML structure
+
+use crate::error::{Error, MediaError};
+use crate::media::ebml::{self, TrackExtras};
+use crate::media::metadata::{
+    bitrate, hdr_format, Attachment, AudioTrack, Chapter, MediaMetadata, SubtitleTrack, VideoTrack,
+};
+use crate::media::MediaProbe;
+use matroska::{Matroska, Settings, Track};
+use std::fs::File;
+
+/// Magic number of EBML header
+const EBML_MAGIC: [u8; 4] = [0x1A, 0x45, 0xDF, 0xA3];
+
+/// Probe of Matroska and WebM files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatroskaProbe;
+
+impl MediaProbe for MatroskaProbe {
+    fn name(&self) -> &'static str {
+        "Matroska"
+    }
+
+    fn matches(&self, header: &[u8]) -> bool {
+        header.starts_with(&EBML_MAGIC)
+    }
+
+    fn probe(&self, file: &mut File) -> Result<MediaMetadata, Error> {
+        let file_size = file
+            .metadata()
+            .map_err(|_| MediaError::IncompleteMetadata)?
+            .len();
+        let matroska = Matroska::open(&mut *file).map_err(MediaError::Matroska)?;
+
+        let duration = matroska
+            .info
+            .duration
+            .ok_or(Error::Media(MediaError::IncompleteMetadata))?;
+
+        // values missing in matroska crate are optional, file is still usable without them
+        let mut extras = ebml::read_track_extras(file).unwrap_or_default();
+
+        let video = match matroska.video_tracks().next() {
+            Some(track) => map_video_track(track, extras.remove(&track.number)),
+            None => return Err(MediaError::NoVideoTrack.into()),
+        };
+
+        let audio_tracks = matroska.audio_tracks().map(map_audio_track).collect();
+        let subtitle_tracks = matroska.subtitle_tracks().map(map_subtitle_track).collect();
+        let attachments = matroska.attachments.iter().map(map_attachment).collect();
+
+        Ok(MediaMetadata {
+            duration,
+            file_size,
+            bitrate: bitrate(file_size, duration),
+            video,
+            audio_tracks,
+            subtitle_tracks,
+            chapters: map_chapters(&matroska.chapters),
+            attachments,
+        })
+    }
+}
+
+fn map_video_track(track: &Track, extras: Option<TrackExtras>) -> VideoTrack {
+    let (width, height) = match &track.settings {
+        Settings::Video(video) => (video.pixel_width, video.pixel_height),
+        _ => (0, 0),
+    };
+    let frame_rate = track
+        .default_duration
+        .filter(|d| !d.is_zero())
+        .map(|d| 1.0 / d.as_secs_f64());
+
+    let TrackExtras {
+        colour,
+        block_addition_types,
+    } = extras.unwrap_or_default();
+
+    VideoTrack {
+        codec: parse_video_codec(track),
+        width,
+        height,
+        frame_rate,
+        bit_depth: colour
+            .as_ref()
+            .and_then(|c| c.bits_per_channel)
+            .filter(|b| *b != 0),
+        hdr: hdr_format(colour.as_ref(), &block_addition_types),
+        colour,
+    }
+}
+
+fn parse_video_codec(track: &Track) -> String {
+    let name = match track.codec_id.as_str() {
+        id if id.contains("HEVC") => "HEVC / h265",
+        id if id.contains("AVC") => "AVC / h264",
+        "V_AV1" => "AV1",
+        "V_VP9" => "VP9",
+        "V_VP8" => "VP8",
+        id => id,
+    };
+
+    name.into()
+}
+
+fn map_audio_track(track: &Track) -> AudioTrack {
+    let (channels, sample_rate) = match &track.settings {
+        Settings::Audio(audio) => (audio.channels, audio.sample_rate),
+        _ => (0, 0.0),
+    };
+
+    AudioTrack {
+        codec: parse_audio_codec(&track.codec_id),
+        name: track.name.clone(),
+        channels,
+        sample_rate,
+        language: track.language.as_ref().map(lang_to_string).cloned(),
+        default: track.default,
+        forced: track.forced,
+    }
+}
+
+fn parse_audio_codec(codec_id: &str) -> String {
+    let name = match codec_id {
+        "A_AC3" => "AC-3",
+        "A_EAC3" => "E-AC-3",
+        "A_TRUEHD" => "TrueHD",
+        "A_FLAC" => "FLAC",
+        "A_OPUS" => "Opus",
+        "A_VORBIS" => "Vorbis",
+        "A_MPEG/L3" => "MP3",
+        id if id.starts_with("A_DTS") => "DTS",
+        id if id.starts_with("A_AAC") => "AAC",
+        id if id.starts_with("A_PCM") => "PCM",
+        id => id,
+    };
+
+    name.into()
+}
+
+fn map_subtitle_track(track: &Track) -> SubtitleTrack {
+    SubtitleTrack {
+        codec: parse_subtitle_codec(&track.codec_id),
+        name: track.name.clone(),
+        language: track.language.as_ref().map(lang_to_string).cloned(),
+        default: track.default,
+        forced: track.forced,
+        hearing_impaired: track.hearing_impaired.unwrap_or_default(),
+    }
+}
+
+fn parse_subtitle_codec(codec_id: &str) -> String {
+    let name = match codec_id {
+        "S_TEXT/UTF8" => "SRT",
+        "S_TEXT/ASS" | "S_ASS" => "ASS",
+        "S_TEXT/SSA" | "S_SSA" => "SSA",
+        "S_TEXT/WEBVTT" => "WebVTT",
+        "S_HDMV/PGS" => "PGS",
+        "S_VOBSUB" => "VobSub",
+        "S_DVBSUB" => "DVB",
+        id => id,
+    };
+
+    name.into()
+}
+
+/// Uses default edition, or the first one when none is marked as default
+fn map_chapters(editions: &[matroska::ChapterEdition]) -> Vec<Chapter> {
+    let edition = editions
+        .iter()
+        .find(|e| e.default && !e.hidden)
+        .or_else(|| editions.first());
+
+    edition
+        .map(|e| e.chapters.as_slice())
+        .unwrap_or_default()
+        .iter()
+        // only hidden chapters are skipped, matroska crate reads missing enabled flag as disabled
+        .filter(|c| !c.hidden)
+        .map(|c| Chapter {
+            title: c.display.first().map(|d| d.string.clone()),
+            start: c.time_start,
+        })
+        .collect()
+}
+
+fn map_attachment(attachment: &matroska::Attachment) -> Attachment {
+    Attachment {
+        name: attachment.name.clone(),
+        mime_type: attachment.mime_type.clone(),
+        description: attachment.description.clone(),
+        size: attachment.data.len(),
+    }
+}
+
+fn lang_to_string(lang: &matroska::Language) -> &String {
+    match lang {
+        matroska::Language::ISO639(s) => s,
+        matroska::Language::IETF(s) => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::metadata::tests::{audio_track, element, float, string, uint, write_file};
+    use crate::media::MediaMetadata;
+    use std::fs::File;
+    use std::time::Duration;
+
+    #[test]
+    fn it_reads_webm_file() {
+        let video = element(
+            0xAE,
+            &[
+                uint(0xD7, 1),
+                uint(0x83, 1),
+                string(0x86, "V_VP9"),
+                element(0xE0, &[uint(0xB0, 1920), uint(0xBA, 800)].concat()),
+            ]
+            .concat(),
+        );
+        let tracks = element(
+            0x1654AE6B,
+            &[video, audio_track(2, "A_OPUS", "eng", true)].concat(),
+        );
+        let info = element(
+            0x1549A966,
+            &[uint(0x2AD7B1, 1_000_000), float(0x4489, 5_400_000.0)].concat(),
+        );
+        let bytes = [
+            element(0x1A45DFA3, &string(0x4282, "webm")),
+            element(0x18538067, &[info, tracks].concat()),
+        ]
+        .concat();
+        let path = write_file("movie.webm", &bytes);
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.duration, Duration::from_secs(5400));
+        assert_eq!(metadata.video.codec, "VP9");
+        assert_eq!((metadata.video.width, metadata.video.height), (1920, 800));
+        assert_eq!(metadata.audio_tracks[0].codec, "Opus");
+    }
+}
@@ -1,66 +1,686 @@
-use crate::error::{Error, MediaError};
-use matroska::{Matroska, Track};
+use crate::error::Error;
+use crate::media::probe;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::time::Duration;
 
+pub use crate::media::ebml::Colour;
+
+/// `BlockAddIDType` of Dolby Vision configuration
+const DOLBY_VISION_TYPES: &[u64] = &[0x64766343, 0x64766643, 0x64767643]; // dvcC, dvwC, dvvC
+/// `BlockAddIDType` of ITU-T T.35 metadata, used for HDR10+
+const ITU_T_T35: u64 = 4;
+
+/// SMPTE ST 2084 transfer characteristics, also known as PQ
+const TRANSFER_PQ: u64 = 16;
+/// ARIB STD-B67 transfer characteristics, also known as HLG
+const TRANSFER_HLG: u64 = 18;
+
 #[derive(Debug)]
 pub struct MediaMetadata {
     pub duration: Duration,
-    pub video_codec: String,
-    pub audio_tracks: Vec<String>,
+    /// Size of the whole file in bytes
+    pub file_size: u64,
+    /// Overall bitrate in bits per second
+    pub bitrate: u64,
+    pub video: VideoTrack,
+    pub audio_tracks: Vec<AudioTrack>,
+    pub subtitle_tracks: Vec<SubtitleTrack>,
+    /// Chapters of default edition
+    pub chapters: Vec<Chapter>,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoTrack {
+    pub codec: String,
+    pub width: u64,
+    pub height: u64,
+    /// Frames per second, when frame duration is constant
+    pub frame_rate: Option<f64>,
+    pub bit_depth: Option<u64>,
+    pub colour: Option<Colour>,
+    pub hdr: Option<HdrFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HdrFormat {
+    DolbyVision,
+    Hdr10Plus,
+    Hdr10,
+    Hlg,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioTrack {
+    pub codec: String,
+    pub name: Option<String>,
+    pub channels: u64,
+    /// Sampling frequency in Hz
+    pub sample_rate: f64,
+    /// ISO 639-2 or IETF BCP 47 code
+    pub language: Option<String>,
+    pub default: bool,
+    pub forced: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtitleTrack {
+    pub codec: String,
+    pub name: Option<String>,
+    /// ISO 639-2 or IETF BCP 47 code
+    pub language: Option<String>,
+    pub default: bool,
+    pub forced: bool,
+    /// Subtitles with descriptions of sounds, also known as SDH
+    pub hearing_impaired: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub title: Option<String>,
+    pub start: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub name: String,
+    pub mime_type: String,
+    pub description: Option<String>,
+    /// Size in bytes
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+    /// Font used by styled subtitles
+    Font,
+    CoverArt,
+    Other,
 }
 
 impl MediaMetadata {
-    pub fn from_file(file: File) -> Result<Self, Error> {
-        let matroska = Matroska::open(file).map_err(MediaError::Matroska)?;
+    /// Reads metadata with [`MediaProbe`](crate::media::MediaProbe) of container detected from
+    /// file header
+    pub fn from_file(mut file: File) -> Result<Self, Error> {
+        probe::find_probe(&mut file)?.probe(&mut file)
+    }
 
-        let duration = matroska
-            .info
-            .duration
-            .ok_or(Error::Media(MediaError::IncompleteMetadata))?;
+    /// Whether there is subtitle track in `language`, only primary language subtag is compared.
+    ///
+    /// ISO 639-1 and both ISO 639-2 codes of the same language match, like `en` and `eng`.
+    pub fn has_subtitles(&self, language: &str) -> bool {
+        let language = primary_language(language);
 
-        let video_codec = match matroska.video_tracks().next() {
-            Some(track) => parse_video_codec(track),
-            None => return Err(MediaError::NoVideoTrack.into()),
-        };
+        self.subtitle_tracks
+            .iter()
+            .filter_map(|t| t.language.as_deref())
+            .any(|l| primary_language(l) == language)
+    }
+}
+
+impl Display for MediaMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let minutes = self.duration.as_secs() / 60;
+
+        write!(
+            f,
+            "{} min, {:.2} GB, {:.1} Mbit/s",
+            minutes,
+            self.file_size as f64 / 1e9,
+            self.bitrate as f64 / 1e6
+        )?;
+        write!(f, "\n{}", self.video)?;
+        for track in &self.audio_tracks {
+            write!(f, "\n{track}")?;
+        }
+        for track in &self.subtitle_tracks {
+            write!(f, "\n{track}")?;
+        }
+        for attachment in &self.attachments {
+            write!(f, "\n{attachment}")?;
+        }
+        if !self.chapters.is_empty() {
+            f.write_str("\nChapters:")?;
+        }
+        for chapter in &self.chapters {
+            write!(f, "\n    {chapter}")?;
+        }
+
+        Ok(())
+    }
+}
 
-        let audio_tracks = matroska.audio_tracks().map(map_audio_track).collect();
+impl Display for VideoTrack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Video: {} {}x{}", self.codec, self.width, self.height)?;
 
-        Ok(Self {
-            duration,
-            video_codec,
-            audio_tracks,
+        if let Some(frame_rate) = self.frame_rate {
+            write!(f, " {frame_rate:.3} fps")?;
+        }
+        if let Some(bit_depth) = self.bit_depth {
+            write!(f, " {bit_depth}-bit")?;
+        }
+        if let Some(hdr) = self.hdr {
+            write!(f, " {hdr}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for HdrFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::DolbyVision => "Dolby Vision",
+            Self::Hdr10Plus => "HDR10+",
+            Self::Hdr10 => "HDR10",
+            Self::Hlg => "HLG",
         })
     }
 }
 
-fn parse_video_codec(track: &Track) -> String {
-    if track.codec_id.contains("HEVC") {
-        String::from("HEVC / h265")
-    } else if track.codec_id.contains("AVC") {
-        String::from("AVC / h264")
-    } else {
-        track.codec_id.clone()
+impl AudioTrack {
+    /// Channel layout, like `5.1`
+    pub fn layout(&self) -> String {
+        match self.channels {
+            1 => "1.0".into(),
+            2 => "2.0".into(),
+            // LFE channel is usual from 6 channels up
+            n if n >= 6 => format!("{}.1", n - 1),
+            n => format!("{n}.0"),
+        }
+    }
+}
+
+impl Display for AudioTrack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Audio: {} {} {} kHz",
+            self.codec,
+            self.layout(),
+            self.sample_rate / 1000.0
+        )?;
+
+        if let Some(language) = &self.language {
+            write!(f, " [{language}]")?;
+        }
+        if let Some(name) = &self.name {
+            write!(f, " \"{name}\"")?;
+        }
+        if self.default {
+            f.write_str(" (default)")?;
+        }
+        if self.forced {
+            f.write_str(" (forced)")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for SubtitleTrack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Subtitles: {}", self.codec)?;
+
+        if let Some(language) = &self.language {
+            write!(f, " [{language}]")?;
+        }
+        if let Some(name) = &self.name {
+            write!(f, " \"{name}\"")?;
+        }
+        if self.default {
+            f.write_str(" (default)")?;
+        }
+        if self.forced {
+            f.write_str(" (forced)")?;
+        }
+        if self.hearing_impaired {
+            f.write_str(" (SDH)")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Chapter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let secs = self.start.as_secs();
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )?;
+
+        if let Some(title) = &self.title {
+            write!(f, " {title}")?;
+        }
+
+        Ok(())
     }
 }
 
-fn map_audio_track(item: &Track) -> String {
-    let name = item.name.as_ref();
-    let lang = item.language.as_ref().map(lang_to_string);
+impl Attachment {
+    pub fn kind(&self) -> AttachmentKind {
+        let mime_type = self.mime_type.to_lowercase();
+        let name = self.name.to_lowercase();
 
-    match (name, lang) {
-        (Some(name), Some(lang)) => {
-            format!("{name} ({lang})")
+        if mime_type.contains("font") || [".ttf", ".otf", ".ttc"].iter().any(|e| name.ends_with(e))
+        {
+            AttachmentKind::Font
+        } else if mime_type.starts_with("image/") && name.starts_with("cover") {
+            AttachmentKind::CoverArt
+        } else {
+            AttachmentKind::Other
         }
-        (None, Some(lang)) => format!("Audio track ({lang})"),
-        (Some(name), None) => name.clone(),
-        (None, None) => "Audio track".into(),
     }
 }
 
-fn lang_to_string(lang: &matroska::Language) -> &String {
-    match lang {
-        matroska::Language::ISO639(s) => s,
-        matroska::Language::IETF(s) => s,
+impl Display for Attachment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind() {
+            AttachmentKind::Font => "font",
+            AttachmentKind::CoverArt => "cover art",
+            AttachmentKind::Other => "file",
+        };
+
+        write!(
+            f,
+            "Attachment: {kind} {} ({}, {} kB)",
+            self.name,
+            self.mime_type,
+            self.size.div_ceil(1000)
+        )
+    }
+}
+
+pub(crate) fn bitrate(file_size: u64, duration: Duration) -> u64 {
+    if duration.is_zero() {
+        return 0;
+    }
+
+    (file_size as f64 * 8.0 / duration.as_secs_f64()) as u64
+}
+
+pub(crate) fn hdr_format(
+    colour: Option<&Colour>,
+    block_addition_types: &[u64],
+) -> Option<HdrFormat> {
+    if block_addition_types
+        .iter()
+        .any(|t| DOLBY_VISION_TYPES.contains(t))
+    {
+        return Some(HdrFormat::DolbyVision);
+    }
+
+    match colour?.transfer_characteristics? {
+        TRANSFER_PQ if block_addition_types.contains(&ITU_T_T35) => Some(HdrFormat::Hdr10Plus),
+        TRANSFER_PQ => Some(HdrFormat::Hdr10),
+        TRANSFER_HLG => Some(HdrFormat::Hlg),
+        _ => None,
+    }
+}
+
+/// ISO 639-1 code with bibliographic and terminologic ISO 639-2 codes of common languages
+const LANGUAGE_CODES: &[(&str, &str, &str)] = &[
+    ("ar", "ara", "ara"),
+    ("bg", "bul", "bul"),
+    ("bn", "ben", "ben"),
+    ("ca", "cat", "cat"),
+    ("cs", "cze", "ces"),
+    ("cy", "wel", "cym"),
+    ("da", "dan", "dan"),
+    ("de", "ger", "deu"),
+    ("el", "gre", "ell"),
+    ("en", "eng", "eng"),
+    ("es", "spa", "spa"),
+    ("et", "est", "est"),
+    ("eu", "baq", "eus"),
+    ("fa", "per", "fas"),
+    ("fi", "fin", "fin"),
+    ("fr", "fre", "fra"),
+    ("ga", "gle", "gle"),
+    ("gl", "glg", "glg"),
+    ("he", "heb", "heb"),
+    ("hi", "hin", "hin"),
+    ("hr", "hrv", "hrv"),
+    ("hu", "hun", "hun"),
+    ("hy", "arm", "hye"),
+    ("id", "ind", "ind"),
+    ("is", "ice", "isl"),
+    ("it", "ita", "ita"),
+    ("ja", "jpn", "jpn"),
+    ("ka", "geo", "kat"),
+    ("ko", "kor", "kor"),
+    ("lt", "lit", "lit"),
+    ("lv", "lav", "lav"),
+    ("mk", "mac", "mkd"),
+    ("ms", "may", "msa"),
+    ("nb", "nob", "nob"),
+    ("nl", "dut", "nld"),
+    ("nn", "nno", "nno"),
+    ("no", "nor", "nor"),
+    ("pl", "pol", "pol"),
+    ("pt", "por", "por"),
+    ("ro", "rum", "ron"),
+    ("ru", "rus", "rus"),
+    ("sk", "slo", "slk"),
+    ("sl", "slv", "slv"),
+    ("sq", "alb", "sqi"),
+    ("sr", "srp", "srp"),
+    ("sv", "swe", "swe"),
+    ("ta", "tam", "tam"),
+    ("th", "tha", "tha"),
+    ("tr", "tur", "tur"),
+    ("uk", "ukr", "ukr"),
+    ("vi", "vie", "vie"),
+    ("zh", "chi", "zho"),
+];
+
+/// Lowercase language without region or script, like `en` for `en-US`.
+///
+/// Known ISO 639-2 codes are replaced by ISO 639-1 code, so `eng` is `en` too.
+fn primary_language(language: &str) -> String {
+    let primary = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(language)
+        .to_ascii_lowercase();
+
+    LANGUAGE_CODES
+        .iter()
+        .find(|(_, bibliographic, terminologic)| {
+            primary == *bibliographic || primary == *terminologic
+        })
+        .map_or(primary, |(code, _, _)| (*code).into())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    /// Encodes element with 8 byte size
+    pub fn element(id: u32, body: &[u8]) -> Vec<u8> {
+        let mut bytes = id
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect::<Vec<_>>();
+        bytes.push(0x01);
+        bytes.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+        bytes.extend_from_slice(body);
+
+        bytes
+    }
+
+    /// Encodes value in as few bytes as possible, like muxers do
+    pub fn uint(id: u32, value: u64) -> Vec<u8> {
+        let bytes = value.to_be_bytes();
+        let zeros = (value.leading_zeros() / 8).min(7) as usize;
+
+        element(id, &bytes[zeros..])
+    }
+
+    pub fn float(id: u32, value: f64) -> Vec<u8> {
+        element(id, &value.to_be_bytes())
+    }
+
+    pub fn string(id: u32, value: &str) -> Vec<u8> {
+        element(id, value.as_bytes())
+    }
+
+    /// Matroska file with 2 hour long segment containing given children
+    pub fn matroska(segment: &[Vec<u8>]) -> Vec<u8> {
+        let info = element(
+            0x1549A966,
+            &[uint(0x2AD7B1, 1_000_000), float(0x4489, 7_200_000.0)].concat(),
+        );
+
+        [
+            element(0x1A45DFA3, &string(0x4282, "matroska")),
+            element(0x18538067, &[&[info], segment].concat().concat()),
+        ]
+        .concat()
+    }
+
+    pub fn video_track() -> Vec<u8> {
+        let colour = element(
+            0x55B0,
+            &[
+                uint(0x55B1, 9),
+                uint(0x55B2, 10),
+                uint(0x55BA, 16),
+                uint(0x55BB, 9),
+                uint(0x55BC, 1000),
+                element(0x55D0, &float(0x55D9, 1000.0)),
+            ]
+            .concat(),
+        );
+        let video = element(0xE0, &[uint(0xB0, 3840), uint(0xBA, 2160), colour].concat());
+
+        element(
+            0xAE,
+            &[
+                uint(0xD7, 1),
+                uint(0x83, 1),
+                string(0x86, "V_MPEGH/ISO/HEVC"),
+                uint(0x23E383, 41_708_333),
+                video,
+            ]
+            .concat(),
+        )
+    }
+
+    pub fn audio_track(number: u64, codec: &str, language: &str, default: bool) -> Vec<u8> {
+        element(
+            0xAE,
+            &[
+                uint(0xD7, number),
+                uint(0x83, 2),
+                string(0x86, codec),
+                string(0x22B59C, language),
+                uint(0x88, default as u64),
+                element(0xE1, &[float(0xB5, 48_000.0), uint(0x9F, 6)].concat()),
+            ]
+            .concat(),
+        )
+    }
+
+    /// Writes file into temporary directory, returns its path
+    pub fn write_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("libmm-{}-{name}", std::process::id()));
+        File::create(&path).unwrap().write_all(bytes).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn it_reads_video_and_audio_tracks() {
+        let tracks = element(
+            0x1654AE6B,
+            &[
+                video_track(),
+                audio_track(2, "A_TRUEHD", "eng", true),
+                audio_track(3, "A_EAC3", "ger", false),
+            ]
+            .concat(),
+        );
+        let bytes = matroska(&[tracks]);
+        let path = write_file("tracks.mkv", &bytes);
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.duration, Duration::from_secs(7200));
+        assert_eq!(metadata.file_size, bytes.len() as u64);
+        assert_eq!(metadata.bitrate, bytes.len() as u64 * 8 / 7200);
+
+        let video = &metadata.video;
+        assert_eq!(video.codec, "HEVC / h265");
+        assert_eq!((video.width, video.height), (3840, 2160));
+        assert_eq!(format!("{:.3}", video.frame_rate.unwrap()), "23.976");
+        assert_eq!(video.bit_depth, Some(10));
+        assert_eq!(video.hdr, Some(HdrFormat::Hdr10));
+        let colour = video.colour.as_ref().unwrap();
+        assert_eq!(colour.primaries, Some(9));
+        assert_eq!(colour.max_cll, Some(1000));
+        assert!(colour.mastering_metadata);
+
+        assert_eq!(metadata.audio_tracks.len(), 2);
+        let audio = &metadata.audio_tracks[1];
+        assert_eq!(audio.codec, "E-AC-3");
+        assert_eq!(audio.layout(), "5.1");
+        assert_eq!(audio.sample_rate, 48_000.0);
+        assert_eq!(audio.language, Some("ger".into()));
+        assert!(metadata.audio_tracks[0].default);
+        assert!(!audio.default);
+        assert!(!audio.forced);
+    }
+
+    fn subtitle_track(
+        number: u64,
+        language: &str,
+        forced: bool,
+        hearing_impaired: bool,
+    ) -> Vec<u8> {
+        element(
+            0xAE,
+            &[
+                uint(0xD7, number),
+                uint(0x83, 0x11),
+                string(0x86, "S_HDMV/PGS"),
+                string(0x22B59C, language),
+                uint(0x88, 0),
+                uint(0x55AA, forced as u64),
+                uint(0x55AB, hearing_impaired as u64),
+            ]
+            .concat(),
+        )
+    }
+
+    fn chapter(uid: u64, start_secs: u64, title: &str) -> Vec<u8> {
+        element(
+            0xB6,
+            &[
+                uint(0x73C4, uid),
+                uint(0x91, start_secs * 1_000_000_000),
+                element(0x80, &[string(0x85, title), string(0x437C, "eng")].concat()),
+            ]
+            .concat(),
+        )
+    }
+
+    fn attachment(name: &str, mime_type: &str, data: &[u8]) -> Vec<u8> {
+        element(
+            0x61A7,
+            &[
+                string(0x466E, name),
+                string(0x4660, mime_type),
+                element(0x465C, data),
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn it_reads_subtitles_chapters_and_attachments() {
+        let tracks = element(
+            0x1654AE6B,
+            &[
+                video_track(),
+                subtitle_track(2, "eng", false, true),
+                subtitle_track(3, "pt-BR", true, false),
+            ]
+            .concat(),
+        );
+        let chapters = element(
+            0x1043A770,
+            &element(
+                0x45B9,
+                &[
+                    uint(0x45DB, 1),
+                    chapter(1, 0, "Opening"),
+                    chapter(2, 3723, "Ending"),
+                ]
+                .concat(),
+            ),
+        );
+        let attachments = element(
+            0x1941A469,
+            &[
+                attachment("Arial.ttf", "application/x-truetype-font", &[0; 1500]),
+                attachment("cover.jpg", "image/jpeg", &[0; 10]),
+            ]
+            .concat(),
+        );
+        let path = write_file("subtitles.mkv", &matroska(&[tracks, chapters, attachments]));
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.subtitle_tracks.len(), 2);
+        let sdh = &metadata.subtitle_tracks[0];
+        assert_eq!(sdh.codec, "PGS");
+        assert!(sdh.hearing_impaired && !sdh.forced && !sdh.default);
+        assert!(metadata.subtitle_tracks[1].forced);
+        assert!(metadata.has_subtitles("ENG"));
+        assert!(metadata.has_subtitles("en"));
+        assert!(metadata.has_subtitles("en-GB"));
+        assert!(metadata.has_subtitles("pt"));
+        assert!(metadata.has_subtitles("por"));
+        assert!(!metadata.has_subtitles("ger"));
+        assert!(!metadata.has_subtitles("de"));
+
+        assert_eq!(
+            metadata.chapters[1],
+            Chapter {
+                title: Some("Ending".into()),
+                start: Duration::from_secs(3723),
+            }
+        );
+        assert_eq!(metadata.chapters[1].to_string(), "01:02:03 Ending");
+
+        assert_eq!(metadata.attachments.len(), 2);
+        assert_eq!(metadata.attachments[0].kind(), AttachmentKind::Font);
+        assert_eq!(metadata.attachments[0].size, 1500);
+        assert_eq!(metadata.attachments[1].kind(), AttachmentKind::CoverArt);
+    }
+
+    #[test]
+    fn it_rejects_unsupported_container() {
+        let path = write_file("movie.mkv", b"FLV\x01\x05\x00\x00\x00\x09");
+
+        let result = MediaMetadata::from_file(File::open(&path).unwrap());
+        std::fs::remove_file(path).unwrap();
+
+        assert!(matches!(
+            result,
+            Err(Error::Media(crate::error::MediaError::UnsupportedContainer))
+        ));
+    }
+
+    #[test]
+    fn it_detects_hdr_format() {
+        let colour = |transfer| Colour {
+            transfer_characteristics: Some(transfer),
+            ..Default::default()
+        };
+
+        assert_eq!(hdr_format(Some(&colour(1)), &[]), None);
+        assert_eq!(hdr_format(Some(&colour(18)), &[]), Some(HdrFormat::Hlg));
+        assert_eq!(
+            hdr_format(Some(&colour(16)), &[ITU_T_T35]),
+            Some(HdrFormat::Hdr10Plus)
+        );
+        assert_eq!(
+            hdr_format(Some(&colour(16)), &[0x64766343]),
+            Some(HdrFormat::DolbyVision)
+        );
+        assert_eq!(hdr_format(None, &[]), None);
     }
 }
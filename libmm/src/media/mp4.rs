@@ -0,0 +1,858 @@
+//! Reader of MP4 and QuickTime files (ISO base media file format)
+
+use crate::error::{Error, MediaError};
+use crate::media::metadata::{
+    bitrate, hdr_format, Attachment, AudioTrack, Chapter, Colour, MediaMetadata, SubtitleTrack,
+    VideoTrack,
+};
+use crate::media::MediaProbe;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// Types of boxes, which may start MP4 and QuickTime files
+const FIRST_BOXES: &[&[u8; 4]] = &[
+    b"ftyp", b"moov", b"mdat", b"free", b"skip", b"wide", b"pnot",
+];
+
+/// Larger `moov` box is surely corrupted, it holds only indexes of samples
+const MAX_MOOV_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Enabled flag of `tkhd` box
+const TRACK_ENABLED: u32 = 0x1;
+
+/// Probe of MP4, M4V and QuickTime files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mp4Probe;
+
+impl MediaProbe for Mp4Probe {
+    fn name(&self) -> &'static str {
+        "MP4"
+    }
+
+    fn matches(&self, header: &[u8]) -> bool {
+        header
+            .get(4..8)
+            .is_some_and(|kind| FIRST_BOXES.iter().any(|b| kind == &b[..]))
+    }
+
+    fn probe(&self, file: &mut File) -> Result<MediaMetadata, Error> {
+        read(file)
+    }
+}
+
+/// Reads metadata of MP4 file from `moov` box
+fn read<R: Read + Seek>(reader: &mut R) -> Result<MediaMetadata, Error> {
+    let file_size = reader.seek(SeekFrom::End(0)).map_err(MediaError::Io)?;
+    let moov = read_moov(reader, file_size).map_err(MediaError::Io)?;
+
+    let movie = parse_movie(&moov).ok_or(MediaError::Io(io::ErrorKind::InvalidData.into()))?;
+    let duration = movie.duration.ok_or(MediaError::IncompleteMetadata)?;
+
+    let mut video = None;
+    let mut audio_tracks = Vec::new();
+    let mut subtitle_tracks = Vec::new();
+
+    for track in movie.tracks {
+        // text tracks referenced as chapters are not subtitles
+        if movie.chapter_tracks.contains(&track.id) {
+            continue;
+        }
+
+        match track.media {
+            Media::Video(v) if video.is_none() => video = Some(v),
+            Media::Audio(a) => audio_tracks.push(a),
+            Media::Subtitle(s) => subtitle_tracks.push(s),
+            _ => {}
+        }
+    }
+
+    Ok(MediaMetadata {
+        duration,
+        file_size,
+        bitrate: bitrate(file_size, duration),
+        video: video.ok_or(MediaError::NoVideoTrack)?,
+        audio_tracks,
+        subtitle_tracks,
+        chapters: movie.chapters,
+        attachments: movie.attachments,
+    })
+}
+
+/// Finds top level `moov` box and reads its body
+fn read_moov<R: Read + Seek>(reader: &mut R, file_size: u64) -> io::Result<Vec<u8>> {
+    let mut position = 0u64;
+
+    while position.saturating_add(8) <= file_size {
+        reader.seek(SeekFrom::Start(position))?;
+
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        let mut size = u64::from(u32::from_be_bytes(header[..4].try_into().unwrap()));
+        let mut header_len = 8;
+
+        if size == 1 {
+            let mut large = [0; 8];
+            reader.read_exact(&mut large)?;
+            size = u64::from_be_bytes(large);
+            header_len = 16;
+        } else if size == 0 {
+            size = file_size - position;
+        }
+
+        if size < header_len {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+
+        if &header[4..] == b"moov" {
+            if size > MAX_MOOV_SIZE {
+                return Err(io::ErrorKind::InvalidData.into());
+            }
+
+            let mut body = vec![0; (size - header_len) as usize];
+            reader.read_exact(&mut body)?;
+
+            return Ok(body);
+        }
+
+        // corrupted size would overflow past the end of any file
+        match position.checked_add(size) {
+            Some(next) => position = next,
+            None => break,
+        }
+    }
+
+    Err(io::ErrorKind::UnexpectedEof.into())
+}
+
+struct Movie {
+    duration: Option<Duration>,
+    tracks: Vec<Track>,
+    /// Ids of tracks used for chapters
+    chapter_tracks: Vec<u32>,
+    chapters: Vec<Chapter>,
+    attachments: Vec<Attachment>,
+}
+
+struct Track {
+    id: u32,
+    media: Media,
+}
+
+enum Media {
+    Video(VideoTrack),
+    Audio(AudioTrack),
+    Subtitle(SubtitleTrack),
+    Other,
+}
+
+fn parse_movie(moov: &[u8]) -> Option<Movie> {
+    let mut movie = Movie {
+        duration: None,
+        tracks: Vec::new(),
+        chapter_tracks: Vec::new(),
+        chapters: Vec::new(),
+        attachments: Vec::new(),
+    };
+
+    for (kind, body) in boxes(moov) {
+        match &kind {
+            b"mvhd" => {
+                let (timescale, duration) = match body.first()? {
+                    1 => (read_u32(body, 20)?, read_u64(body, 24)?),
+                    _ => (read_u32(body, 12)?, u64::from(read_u32(body, 16)?)),
+                };
+                movie.duration = to_duration(duration, timescale);
+            }
+            b"trak" => {
+                // malformed track is skipped, others may be still usable
+                if let Some((track, chapters)) = parse_track(body) {
+                    movie.chapter_tracks.extend(chapters);
+                    movie.tracks.push(track);
+                }
+            }
+            b"udta" => parse_user_data(body, &mut movie),
+            _ => {}
+        }
+    }
+
+    Some(movie)
+}
+
+/// Returns track and ids of its chapter tracks
+fn parse_track(trak: &[u8]) -> Option<(Track, Vec<u32>)> {
+    let tkhd = find_box(trak, b"tkhd")?;
+    let id = match tkhd.first()? {
+        1 => read_u32(tkhd, 20)?,
+        _ => read_u32(tkhd, 12)?,
+    };
+    let enabled = read_u32(tkhd, 0)? & 0xFF_FFFF & TRACK_ENABLED != 0;
+
+    let chapters = find_box(trak, b"tref")
+        .and_then(|tref| find_box(tref, b"chap"))
+        .map(|chap| {
+            chap.chunks_exact(4)
+                .filter_map(|c| read_u32(c, 0))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mdia = find_box(trak, b"mdia")?;
+    let mdhd = find_box(mdia, b"mdhd")?;
+    let (timescale, language) = match mdhd.first()? {
+        1 => (read_u32(mdhd, 20)?, read_u16(mdhd, 32)?),
+        _ => (read_u32(mdhd, 12)?, read_u16(mdhd, 20)?),
+    };
+    let language = find_box(mdia, b"elng")
+        .and_then(|elng| read_string(elng.get(4..)?))
+        .or_else(|| unpack_language(language));
+
+    let handler: [u8; 4] = find_box(mdia, b"hdlr")?.get(8..12)?.try_into().ok()?;
+
+    let stbl = find_box(find_box(mdia, b"minf")?, b"stbl")?;
+    let stsd = find_box(stbl, b"stsd")?;
+    let (format, entry) = boxes(stsd.get(8..)?).next()?;
+
+    let media = match &handler {
+        b"vide" => {
+            let frame_rate = find_box(stbl, b"stts").and_then(|stts| frame_rate(stts, timescale));
+            Media::Video(parse_video_entry(format, entry, frame_rate)?)
+        }
+        b"soun" => Media::Audio(parse_audio_entry(format, entry, language, enabled)?),
+        b"sbtl" | b"subt" | b"text" | b"clcp" => Media::Subtitle(SubtitleTrack {
+            codec: subtitle_codec(&format),
+            name: None,
+            language,
+            default: enabled,
+            forced: false,
+            hearing_impaired: false,
+        }),
+        _ => Media::Other,
+    };
+
+    Some((Track { id, media }, chapters))
+}
+
+fn parse_video_entry(format: [u8; 4], entry: &[u8], frame_rate: Option<f64>) -> Option<VideoTrack> {
+    let width = u64::from(read_u16(entry, 24)?);
+    let height = u64::from(read_u16(entry, 26)?);
+
+    let mut colour = None::<Colour>;
+    let mut bit_depth = None;
+    let mut block_addition_types = Vec::new();
+
+    for (kind, body) in boxes(entry.get(78..)?) {
+        match &kind {
+            b"colr" if matches!(body.get(..4), Some(b"nclx" | b"nclc")) => {
+                let colour = colour.get_or_insert_with(Colour::default);
+                colour.primaries = read_u16(body, 4).map(u64::from);
+                colour.transfer_characteristics = read_u16(body, 6).map(u64::from);
+                colour.matrix_coefficients = read_u16(body, 8).map(u64::from);
+            }
+            b"clli" => {
+                let colour = colour.get_or_insert_with(Colour::default);
+                colour.max_cll = read_u16(body, 0).map(u64::from);
+                colour.max_fall = read_u16(body, 2).map(u64::from);
+            }
+            b"mdcv" => {
+                colour
+                    .get_or_insert_with(Colour::default)
+                    .mastering_metadata = true
+            }
+            b"hvcC" => bit_depth = body.get(17).map(|b| u64::from(b & 0x07) + 8),
+            b"av1C" => {
+                bit_depth = body.get(2).map(|b| match (b & 0x40 != 0, b & 0x20 != 0) {
+                    (true, true) => 12,
+                    (true, false) => 10,
+                    _ => 8,
+                })
+            }
+            b"dvcC" | b"dvvC" | b"dvwC" => {
+                block_addition_types.push(u64::from(u32::from_be_bytes(kind)))
+            }
+            _ => {}
+        }
+    }
+
+    if let (Some(colour), Some(bit_depth)) = (&mut colour, bit_depth) {
+        colour.bits_per_channel = Some(bit_depth);
+    }
+
+    Some(VideoTrack {
+        codec: video_codec(&format),
+        width,
+        height,
+        frame_rate,
+        bit_depth,
+        hdr: hdr_format(colour.as_ref(), &block_addition_types),
+        colour,
+    })
+}
+
+fn parse_audio_entry(
+    format: [u8; 4],
+    entry: &[u8],
+    language: Option<String>,
+    enabled: bool,
+) -> Option<AudioTrack> {
+    // QuickTime sound descriptions of newer versions have more fields before child boxes
+    let (mut channels, sample_rate, children) = match read_u16(entry, 8)? {
+        2 => (
+            u64::from(read_u32(entry, 40)?),
+            f64::from_bits(read_u64(entry, 32)?),
+            64,
+        ),
+        version => (
+            u64::from(read_u16(entry, 16)?),
+            f64::from(read_u32(entry, 24)? >> 16),
+            if version == 1 { 44 } else { 28 },
+        ),
+    };
+
+    let mut codec = audio_codec(&format);
+
+    for (kind, body) in boxes(entry.get(children..).unwrap_or_default()) {
+        match &kind {
+            b"esds" => {
+                if let Some(name) = esds_codec(body) {
+                    codec = name.into();
+                }
+            }
+            b"dac3" => {
+                if let Some(count) = ac3_channels(body) {
+                    channels = count;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(AudioTrack {
+        codec,
+        name: None,
+        channels,
+        sample_rate,
+        language,
+        default: enabled,
+        forced: false,
+    })
+}
+
+/// Nero chapters and iTunes cover art
+fn parse_user_data(udta: &[u8], movie: &mut Movie) {
+    for (kind, body) in boxes(udta) {
+        match &kind {
+            b"chpl" => movie.chapters = nero_chapters(body).unwrap_or_default(),
+            b"meta" => {
+                // `meta` is full box in MP4, but not in QuickTime
+                let children = match body.get(4..8) {
+                    Some(b"hdlr") => body,
+                    _ => body.get(4..).unwrap_or_default(),
+                };
+
+                let covers = find_box(children, b"ilst")
+                    .and_then(|ilst| find_box(ilst, b"covr"))
+                    .unwrap_or_default();
+
+                for (kind, data) in boxes(covers) {
+                    if &kind == b"data" {
+                        movie.attachments.extend(cover_art(data));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn nero_chapters(chpl: &[u8]) -> Option<Vec<Chapter>> {
+    let mut offset = if chpl.first()? == &1 { 8 } else { 4 };
+    let count = *chpl.get(offset)?;
+    offset += 1;
+
+    let mut chapters = Vec::new();
+    for _ in 0..count {
+        // start is in units of 100 ns
+        let start = read_u64(chpl, offset)?;
+        let len = usize::from(*chpl.get(offset + 8)?);
+        let title = String::from_utf8_lossy(chpl.get(offset + 9..offset + 9 + len)?);
+
+        chapters.push(Chapter {
+            title: Some(title.into_owned()).filter(|t| !t.is_empty()),
+            start: Duration::from_nanos(start.saturating_mul(100)),
+        });
+        offset += 9 + len;
+    }
+
+    Some(chapters)
+}
+
+fn cover_art(data: &[u8]) -> Option<Attachment> {
+    let (name, mime_type) = match read_u32(data, 0)? & 0xFF_FFFF {
+        13 => ("cover.jpg", "image/jpeg"),
+        14 => ("cover.png", "image/png"),
+        27 => ("cover.bmp", "image/bmp"),
+        _ => return None,
+    };
+
+    Some(Attachment {
+        name: name.into(),
+        mime_type: mime_type.into(),
+        description: None,
+        size: data.len().saturating_sub(8),
+    })
+}
+
+/// Most common duration of samples is used, last frame is often shorter
+fn frame_rate(stts: &[u8], timescale: u32) -> Option<f64> {
+    // count is read from file, so it is limited by number of entries fitting in the box
+    let count = (read_u32(stts, 4)? as usize).min(stts.len().saturating_sub(8) / 8);
+
+    let delta = (0..count)
+        .filter_map(|i| Some((read_u32(stts, 8 + i * 8)?, read_u32(stts, 12 + i * 8)?)))
+        .max_by_key(|(samples, _)| *samples)
+        .map(|(_, delta)| delta)
+        .filter(|delta| *delta != 0)?;
+
+    Some(f64::from(timescale) / f64::from(delta))
+}
+
+/// Object type indication of decoder config descriptor, distinguishes AAC from MP3
+fn esds_codec(esds: &[u8]) -> Option<&'static str> {
+    let mut offset = 4;
+
+    // ES descriptor with its id and flags
+    if *esds.get(offset)? != 0x03 {
+        return None;
+    }
+    offset = skip_descriptor_size(esds, offset + 1)?;
+    let flags = *esds.get(offset + 2)?;
+    offset += 3;
+    if flags & 0x80 != 0 {
+        offset += 2;
+    }
+    if flags & 0x40 != 0 {
+        offset += 1 + usize::from(*esds.get(offset)?);
+    }
+    if flags & 0x20 != 0 {
+        offset += 2;
+    }
+
+    // decoder config descriptor
+    if *esds.get(offset)? != 0x04 {
+        return None;
+    }
+    offset = skip_descriptor_size(esds, offset + 1)?;
+
+    match esds.get(offset)? {
+        0x40 | 0x66..=0x68 => Some("AAC"),
+        0x69 | 0x6B => Some("MP3"),
+        0xA5 => Some("AC-3"),
+        0xA6 => Some("E-AC-3"),
+        0xA9 => Some("DTS"),
+        0xAD => Some("Opus"),
+        _ => None,
+    }
+}
+
+/// Size of descriptors has 1 to 4 bytes, returns offset after it
+fn skip_descriptor_size(data: &[u8], mut offset: usize) -> Option<usize> {
+    for _ in 0..4 {
+        let byte = *data.get(offset)?;
+        offset += 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    Some(offset)
+}
+
+/// AC-3 sample entries always claim 2 channels, real count is in `dac3` box
+fn ac3_channels(dac3: &[u8]) -> Option<u64> {
+    let bits = read_u16(dac3, 0)?;
+    let acmod = (bits >> 3) & 0x07;
+    let lfeon = (bits >> 2) & 0x01;
+
+    let channels = [2, 1, 2, 3, 3, 4, 4, 5][acmod as usize];
+
+    Some(channels + u64::from(lfeon))
+}
+
+fn video_codec(format: &[u8; 4]) -> String {
+    let name = match format {
+        b"avc1" | b"avc3" => "AVC / h264",
+        b"hvc1" | b"hev1" | b"dvh1" | b"dvhe" => "HEVC / h265",
+        b"av01" => "AV1",
+        b"vp09" => "VP9",
+        b"mp4v" => "MPEG-4 Visual",
+        b"apcn" | b"apch" | b"apcs" | b"apco" | b"ap4h" => "ProRes",
+        _ => return String::from_utf8_lossy(format).into_owned(),
+    };
+
+    name.into()
+}
+
+fn audio_codec(format: &[u8; 4]) -> String {
+    let name = match format {
+        b"mp4a" => "AAC",
+        b"ac-3" => "AC-3",
+        b"ec-3" => "E-AC-3",
+        b"ac-4" => "AC-4",
+        b"mlpa" => "TrueHD",
+        b"dtsc" | b"dtsh" | b"dtsl" | b"dtse" | b"dtsx" => "DTS",
+        b"Opus" => "Opus",
+        b"fLaC" => "FLAC",
+        b"alac" => "ALAC",
+        b".mp3" => "MP3",
+        b"lpcm" | b"sowt" | b"twos" | b"ipcm" | b"in24" => "PCM",
+        _ => return String::from_utf8_lossy(format).into_owned(),
+    };
+
+    name.into()
+}
+
+fn subtitle_codec(format: &[u8; 4]) -> String {
+    let name = match format {
+        b"tx3g" | b"text" => "mov_text",
+        b"wvtt" => "WebVTT",
+        b"stpp" => "TTML",
+        b"c608" => "CEA-608",
+        b"c708" => "CEA-708",
+        _ => return String::from_utf8_lossy(format).into_owned(),
+    };
+
+    name.into()
+}
+
+/// ISO 639-2 code packed into 3 × 5 bits, QuickTime language codes are ignored
+fn unpack_language(packed: u16) -> Option<String> {
+    if packed < 0x400 {
+        return None;
+    }
+
+    let language = [10, 5, 0]
+        .iter()
+        .map(|shift| char::from(((packed >> shift) & 0x1F) as u8 + 0x60))
+        .collect::<String>();
+
+    (language != "und").then_some(language)
+}
+
+fn to_duration(duration: u64, timescale: u32) -> Option<Duration> {
+    if timescale == 0 || duration == u64::MAX || duration == u64::from(u32::MAX) {
+        return None;
+    }
+
+    Duration::try_from_secs_f64(duration as f64 / f64::from(timescale)).ok()
+}
+
+/// Iterates over boxes in `data`, returning their type and body
+fn boxes(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    let mut rest = data;
+
+    std::iter::from_fn(move || {
+        let size = read_u32(rest, 0)? as usize;
+        let kind: [u8; 4] = rest.get(4..8)?.try_into().ok()?;
+
+        let (header_len, size) = match size {
+            0 => (8, rest.len()),
+            1 => (16, usize::try_from(read_u64(rest, 8)?).ok()?),
+            size => (8, size),
+        };
+
+        let body = rest.get(header_len..size)?;
+        rest = &rest[size..];
+
+        Some((kind, body))
+    })
+}
+
+fn find_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).find(|(k, _)| k == kind).map(|(_, body)| body)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// Null terminated string
+fn read_string(data: &[u8]) -> Option<String> {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+
+    Some(String::from_utf8_lossy(&data[..end]).into_owned()).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::metadata::tests::write_file;
+    use crate::media::{AttachmentKind, HdrFormat};
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [&(body.len() as u32 + 8).to_be_bytes(), kind, body].concat()
+    }
+
+    fn full_box(kind: &[u8; 4], flags: u32, body: &[u8]) -> Vec<u8> {
+        mp4_box(kind, &[&flags.to_be_bytes(), body].concat())
+    }
+
+    fn pack_language(language: &str) -> [u8; 2] {
+        let packed = language
+            .bytes()
+            .fold(0u16, |packed, c| (packed << 5) | u16::from(c - 0x60));
+
+        packed.to_be_bytes()
+    }
+
+    /// Track with version 0 headers and single sample entry
+    fn track(id: u32, enabled: bool, handler: &[u8; 4], language: &str, entry: Vec<u8>) -> Vec<u8> {
+        let tkhd = full_box(
+            b"tkhd",
+            enabled as u32,
+            &[&[0; 8][..], &id.to_be_bytes(), &[0; 68]].concat(),
+        );
+        let mdhd = full_box(
+            b"mdhd",
+            0,
+            &[
+                &[0; 8][..],
+                &24_000u32.to_be_bytes(),
+                &[0; 4],
+                &pack_language(language),
+                &[0; 2],
+            ]
+            .concat(),
+        );
+        let hdlr = full_box(b"hdlr", 0, &[&[0; 4][..], handler, &[0; 13]].concat());
+        let stts = full_box(
+            b"stts",
+            0,
+            &[2u32, 1000, 1001, 1, 500]
+                .iter()
+                .flat_map(|v| v.to_be_bytes())
+                .collect::<Vec<_>>(),
+        );
+        let stsd = full_box(b"stsd", 0, &[&1u32.to_be_bytes()[..], &entry].concat());
+        let stbl = mp4_box(b"stbl", &[stsd, stts].concat());
+        let minf = mp4_box(b"minf", &stbl);
+        let mdia = mp4_box(b"mdia", &[mdhd, hdlr, minf].concat());
+
+        mp4_box(b"trak", &[tkhd, mdia].concat())
+    }
+
+    fn video_entry() -> Vec<u8> {
+        let mut hvcc = vec![0; 23];
+        hvcc[17] = 0xFA;
+        let colr = mp4_box(b"colr", &[b"nclx", &[0, 9, 0, 16, 0, 9, 0][..]].concat());
+        let clli = mp4_box(b"clli", &[0x03, 0xE8, 0x01, 0x90]);
+        let mdcv = mp4_box(b"mdcv", &[0; 24]);
+
+        let mut header = vec![0; 78];
+        header[24..26].copy_from_slice(&3840u16.to_be_bytes());
+        header[26..28].copy_from_slice(&2160u16.to_be_bytes());
+
+        mp4_box(
+            b"hvc1",
+            &[header, mp4_box(b"hvcC", &hvcc), colr, clli, mdcv].concat(),
+        )
+    }
+
+    fn audio_entry(format: &[u8; 4], child: Vec<u8>) -> Vec<u8> {
+        let mut header = vec![0; 28];
+        header[16..18].copy_from_slice(&2u16.to_be_bytes());
+        header[24..28].copy_from_slice(&(48_000u32 << 16).to_be_bytes());
+
+        mp4_box(format, &[header, child].concat())
+    }
+
+    fn text_entry(format: &[u8; 4]) -> Vec<u8> {
+        mp4_box(format, &[0; 8])
+    }
+
+    fn user_data() -> Vec<u8> {
+        let chapter = |start: u64, title: &str| {
+            [
+                &start.to_be_bytes()[..],
+                &[title.len() as u8],
+                title.as_bytes(),
+            ]
+            .concat()
+        };
+        let chpl = full_box(
+            b"chpl",
+            0x0100_0000,
+            &[
+                &[0; 4][..],
+                &[2],
+                &chapter(0, "Opening"),
+                &chapter(6_000_000_000, "Ending"),
+            ]
+            .concat(),
+        );
+
+        let data = full_box(b"data", 13, &[&[0; 4][..], &[0xFF; 100]].concat());
+        let ilst = mp4_box(b"ilst", &mp4_box(b"covr", &data));
+        let hdlr = full_box(b"hdlr", 0, &[&[0; 4][..], b"mdir", &[0; 13]].concat());
+        let meta = full_box(b"meta", 0, &[hdlr, ilst].concat());
+
+        mp4_box(b"udta", &[chpl, meta].concat())
+    }
+
+    #[test]
+    fn it_reads_mp4_file() {
+        let mvhd = full_box(
+            b"mvhd",
+            0,
+            &[
+                &[0; 8][..],
+                &1000u32.to_be_bytes(),
+                &7_200_000u32.to_be_bytes(),
+                &[0; 80],
+            ]
+            .concat(),
+        );
+
+        let mut video = track(1, true, b"vide", "und", video_entry());
+        // video references chapter track, which must not be listed as subtitles
+        video.extend(mp4_box(b"tref", &mp4_box(b"chap", &5u32.to_be_bytes())));
+        let video = mp4_box(b"trak", &video[8..]);
+
+        let dac3 = mp4_box(b"dac3", &[0x10, 0x3C, 0x00]);
+        let esds = full_box(
+            b"esds",
+            0,
+            &[
+                0x03, 0x19, 0x00, 0x01, 0x00, 0x04, 0x11, 0x6B, 0x15, 0, 0, 0,
+            ],
+        );
+
+        let moov = mp4_box(
+            b"moov",
+            &[
+                mvhd,
+                video,
+                track(2, true, b"soun", "eng", audio_entry(b"ac-3", dac3)),
+                track(3, false, b"soun", "ger", audio_entry(b"mp4a", esds)),
+                track(4, false, b"sbtl", "cze", text_entry(b"tx3g")),
+                track(5, false, b"text", "eng", text_entry(b"text")),
+                user_data(),
+            ]
+            .concat(),
+        );
+
+        // index stored at the end of file, like without fast start
+        let bytes = [
+            mp4_box(b"ftyp", b"isom\x00\x00\x02\x00isomiso2"),
+            mp4_box(b"mdat", &[0; 1000]),
+            moov,
+        ]
+        .concat();
+        let path = write_file("movie.mp4", &bytes);
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.duration, Duration::from_secs(7200));
+        assert_eq!(metadata.file_size, bytes.len() as u64);
+
+        let video = &metadata.video;
+        assert_eq!(video.codec, "HEVC / h265");
+        assert_eq!((video.width, video.height), (3840, 2160));
+        assert_eq!(format!("{:.3}", video.frame_rate.unwrap()), "23.976");
+        assert_eq!(video.bit_depth, Some(10));
+        assert_eq!(video.hdr, Some(HdrFormat::Hdr10));
+        let colour = video.colour.as_ref().unwrap();
+        assert_eq!(colour.primaries, Some(9));
+        assert_eq!(colour.max_cll, Some(1000));
+        assert_eq!(colour.max_fall, Some(400));
+        assert!(colour.mastering_metadata);
+
+        assert_eq!(metadata.audio_tracks.len(), 2);
+        let ac3 = &metadata.audio_tracks[0];
+        assert_eq!(ac3.codec, "AC-3");
+        assert_eq!(ac3.layout(), "5.1");
+        assert_eq!(ac3.sample_rate, 48_000.0);
+        assert_eq!(ac3.language.as_deref(), Some("eng"));
+        assert!(ac3.default);
+        let mp3 = &metadata.audio_tracks[1];
+        assert_eq!(mp3.codec, "MP3");
+        assert_eq!(mp3.channels, 2);
+        assert_eq!(mp3.language.as_deref(), Some("ger"));
+        assert!(!mp3.default);
+
+        assert_eq!(metadata.subtitle_tracks.len(), 1);
+        assert_eq!(metadata.subtitle_tracks[0].codec, "mov_text");
+        assert!(metadata.has_subtitles("cze"));
+        assert!(metadata.has_subtitles("ces"));
+        assert!(metadata.has_subtitles("cs"));
+
+        assert_eq!(
+            metadata.chapters,
+            vec![
+                Chapter {
+                    title: Some("Opening".into()),
+                    start: Duration::ZERO,
+                },
+                Chapter {
+                    title: Some("Ending".into()),
+                    start: Duration::from_secs(600),
+                },
+            ]
+        );
+
+        assert_eq!(metadata.attachments.len(), 1);
+        assert_eq!(metadata.attachments[0].kind(), AttachmentKind::CoverArt);
+        assert_eq!(metadata.attachments[0].size, 100);
+    }
+
+    #[test]
+    fn it_unpacks_language() {
+        assert_eq!(
+            unpack_language(u16::from_be_bytes(pack_language("fre"))).as_deref(),
+            Some("fre")
+        );
+        assert_eq!(
+            unpack_language(u16::from_be_bytes(pack_language("und"))),
+            None
+        );
+        // QuickTime language code of English
+        assert_eq!(unpack_language(0), None);
+    }
+
+    #[test]
+    fn it_survives_corrupted_sizes() {
+        let huge_box = [&1u32.to_be_bytes()[..], b"ftyp", &u64::MAX.to_be_bytes()].concat();
+        let res = read_moov(&mut std::io::Cursor::new(&huge_box), 1 << 20);
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let chpl = [&[0, 0, 0, 0, 1][..], &u64::MAX.to_be_bytes(), &[0]].concat();
+        let chapters = nero_chapters(&chpl).unwrap();
+        assert_eq!(chapters[0].start, Duration::from_nanos(u64::MAX));
+
+        let stts = [
+            &[0; 4][..],
+            &u32::MAX.to_be_bytes(),
+            &[0, 0, 0, 1, 0, 0, 3, 233],
+        ]
+        .concat();
+        assert_eq!(frame_rate(&stts, 24_000), Some(24_000.0 / 1001.0));
+
+        // version 1 mvhd with duration too long for Duration
+        assert_eq!(to_duration(u64::MAX - 1, 1), None);
+    }
+}
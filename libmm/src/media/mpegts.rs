@@ -0,0 +1,1057 @@
+//! Reader of MPEG transport streams, including Blu-ray M2TS files
+//!
+//! Streams are listed in program map table, their properties are read from the first frames.
+//! Duration is computed from program clock references at the start and at the end of file.
+
+use crate::error::{Error, MediaError};
+use crate::media::metadata::{bitrate, AudioTrack, MediaMetadata, SubtitleTrack, VideoTrack};
+use crate::media::probe::HEADER_LEN;
+use crate::media::{HdrFormat, MediaProbe};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+const SYNC_BYTE: u8 = 0x47;
+const PACKET_LEN: usize = 188;
+/// M2TS packets are prefixed by 4 byte timestamp
+const M2TS_PACKET_LEN: usize = 192;
+
+/// Amount of data read from the start and from the end of file
+const SCAN_SIZE: u64 = 8 * 1024 * 1024;
+/// Amount of elementary stream data collected to find headers of video and audio
+const VIDEO_DATA_LEN: usize = 512 * 1024;
+const AUDIO_DATA_LEN: usize = 16 * 1024;
+
+const PAT_PID: u16 = 0;
+/// Frequency of clock references without extension
+const CLOCK_FREQUENCY: u64 = 90_000;
+
+const ISO_639_LANGUAGE_DESCRIPTOR: u8 = 0x0A;
+const REGISTRATION_DESCRIPTOR: u8 = 0x05;
+const SUBTITLING_DESCRIPTOR: u8 = 0x59;
+const TELETEXT_DESCRIPTOR: u8 = 0x56;
+const AC3_DESCRIPTOR: u8 = 0x6A;
+const EAC3_DESCRIPTOR: u8 = 0x7A;
+const DTS_DESCRIPTOR: u8 = 0x7B;
+const DOVI_VIDEO_DESCRIPTOR: u8 = 0xB0;
+
+/// Probe of MPEG-TS and M2TS files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MpegTsProbe;
+
+impl MediaProbe for MpegTsProbe {
+    fn name(&self) -> &'static str {
+        "MPEG-TS"
+    }
+
+    fn matches(&self, header: &[u8]) -> bool {
+        packet_format(header).is_some()
+    }
+
+    fn probe(&self, file: &mut File) -> Result<MediaMetadata, Error> {
+        read(file)
+    }
+}
+
+/// Length and offset of sync byte of packets, at least 2 packets have to be in `data`
+fn packet_format(data: &[u8]) -> Option<(usize, usize)> {
+    [(PACKET_LEN, 0), (M2TS_PACKET_LEN, 4)]
+        .into_iter()
+        .find(|(len, offset)| {
+            let mut syncs = data.iter().skip(*offset).step_by(*len);
+            data.len() > offset + len && syncs.all(|b| *b == SYNC_BYTE)
+        })
+}
+
+fn read<R: Read + Seek>(reader: &mut R) -> Result<MediaMetadata, Error> {
+    let file_size = reader.seek(SeekFrom::End(0)).map_err(MediaError::Io)?;
+
+    let start = read_at(reader, 0).map_err(MediaError::Io)?;
+    let (len, offset) = packet_format(&start[..start.len().min(HEADER_LEN)])
+        .ok_or(MediaError::UnsupportedContainer)?;
+    let packets = || {
+        start
+            .chunks_exact(len)
+            .filter_map(|p| Packet::parse(&p[offset..]))
+    };
+
+    let pmt_pid = packets()
+        .filter(|p| p.pid == PAT_PID && p.unit_start)
+        .find_map(|p| parse_pat(section(p.payload)?))
+        .ok_or(MediaError::IncompleteMetadata)?;
+    let program = packets()
+        .filter(|p| p.pid == pmt_pid && p.unit_start)
+        .find_map(|p| parse_pmt(section(p.payload)?))
+        .ok_or(MediaError::IncompleteMetadata)?;
+
+    let data = collect_stream_data(packets(), &program.streams);
+
+    let end = read_at(
+        reader,
+        file_size.saturating_sub(SCAN_SIZE) / len as u64 * len as u64,
+    )
+    .map_err(MediaError::Io)?;
+    let first_pcr = packets().find_map(|p| p.pcr.filter(|_| p.pid == program.pcr_pid));
+    let last_pcr = end
+        .chunks_exact(len)
+        .rev()
+        .filter_map(|p| Packet::parse(&p[offset..]))
+        .find_map(|p| p.pcr.filter(|_| p.pid == program.pcr_pid));
+
+    let duration = match (first_pcr, last_pcr) {
+        // clock references have 33 bits and may wrap around
+        (Some(first), Some(last)) => Duration::from_secs_f64(
+            (last.wrapping_sub(first) & ((1 << 33) - 1)) as f64 / CLOCK_FREQUENCY as f64,
+        ),
+        _ => return Err(MediaError::IncompleteMetadata.into()),
+    };
+
+    let mut video = None;
+    let mut audio_tracks = Vec::new();
+    let mut subtitle_tracks = Vec::new();
+
+    for stream in &program.streams {
+        let data = data.get(&stream.pid).map(Vec::as_slice).unwrap_or_default();
+
+        match stream.kind() {
+            Some(Kind::Video(codec)) if video.is_none() => {
+                video = Some(video_track(stream, codec, data))
+            }
+            Some(Kind::Audio(codec)) => audio_tracks.push(audio_track(stream, codec, data)),
+            Some(Kind::Subtitle(codec)) => subtitle_tracks.push(subtitle_track(stream, codec)),
+            _ => {}
+        }
+    }
+
+    Ok(MediaMetadata {
+        duration,
+        file_size,
+        bitrate: bitrate(file_size, duration),
+        video: video.ok_or(MediaError::NoVideoTrack)?,
+        audio_tracks,
+        subtitle_tracks,
+        chapters: Vec::new(),
+        attachments: Vec::new(),
+    })
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, position: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.seek(SeekFrom::Start(position))?;
+    reader.take(SCAN_SIZE).read_to_end(&mut data)?;
+
+    Ok(data)
+}
+
+struct Packet<'a> {
+    pid: u16,
+    /// Packet starts new PES packet or section
+    unit_start: bool,
+    /// Base of program clock reference, in 90 kHz units
+    pcr: Option<u64>,
+    payload: &'a [u8],
+}
+
+impl<'a> Packet<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if *data.first()? != SYNC_BYTE {
+            return None;
+        }
+
+        let pid = u16::from_be_bytes([data[1] & 0x1F, *data.get(2)?]);
+        let unit_start = data[1] & 0x40 != 0;
+        let control = data.get(3)? >> 4 & 0x03;
+
+        let mut pcr = None;
+        let mut payload_start = 4;
+        if control & 0x02 != 0 {
+            let len = usize::from(*data.get(4)?);
+            let adaptation = data.get(5..5 + len)?;
+            if adaptation.first().is_some_and(|flags| flags & 0x10 != 0) {
+                let pcr_bytes = adaptation.get(1..6)?;
+                pcr = Some(
+                    pcr_bytes[..4]
+                        .iter()
+                        .fold(0, |pcr, b| pcr << 8 | u64::from(*b))
+                        << 1
+                        | u64::from(pcr_bytes[4] >> 7),
+                );
+            }
+            payload_start = 5 + len;
+        }
+
+        let payload = match control & 0x01 {
+            0 => &[],
+            _ => data.get(payload_start..PACKET_LEN)?,
+        };
+
+        Some(Self {
+            pid,
+            unit_start,
+            pcr,
+            payload,
+        })
+    }
+}
+
+/// Section without its header and CRC, when it starts in payload and fits into it
+fn section(payload: &[u8]) -> Option<(u8, &[u8])> {
+    let pointer = usize::from(*payload.first()?);
+    let section = payload.get(1 + pointer..)?;
+
+    let table_id = *section.first()?;
+    let len = usize::from(u16::from_be_bytes([
+        section.get(1)? & 0x0F,
+        *section.get(2)?,
+    ]));
+
+    Some((table_id, section.get(8..(3 + len).checked_sub(4)?)?))
+}
+
+/// Returns PID of program map table of the first program
+fn parse_pat((table_id, body): (u8, &[u8])) -> Option<u16> {
+    if table_id != 0x00 {
+        return None;
+    }
+
+    body.chunks_exact(4)
+        // program number 0 points to network information table
+        .find(|p| p[..2] != [0, 0])
+        .map(|p| u16::from_be_bytes([p[2] & 0x1F, p[3]]))
+}
+
+struct Program {
+    pcr_pid: u16,
+    streams: Vec<Stream>,
+}
+
+struct Stream {
+    pid: u16,
+    stream_type: u8,
+    descriptors: Vec<(u8, Vec<u8>)>,
+}
+
+fn parse_pmt((table_id, body): (u8, &[u8])) -> Option<Program> {
+    if table_id != 0x02 {
+        return None;
+    }
+
+    let pcr_pid = u16::from_be_bytes([body.first()? & 0x1F, *body.get(1)?]);
+    let info_len = usize::from(u16::from_be_bytes([body.get(2)? & 0x0F, *body.get(3)?]));
+
+    let mut streams = Vec::new();
+    let mut rest = body.get(4 + info_len..)?;
+    while rest.len() >= 5 {
+        let pid = u16::from_be_bytes([rest[1] & 0x1F, rest[2]]);
+        let len = usize::from(u16::from_be_bytes([rest[3] & 0x0F, rest[4]]));
+
+        streams.push(Stream {
+            pid,
+            stream_type: rest[0],
+            descriptors: descriptors(rest.get(5..5 + len)?),
+        });
+        rest = &rest[5 + len..];
+    }
+
+    Some(Program { pcr_pid, streams })
+}
+
+fn descriptors(mut data: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut descriptors = Vec::new();
+
+    while let [tag, len, rest @ ..] = data {
+        let Some(body) = rest.get(..usize::from(*len)) else {
+            break;
+        };
+        descriptors.push((*tag, body.to_vec()));
+        data = &rest[body.len()..];
+    }
+
+    descriptors
+}
+
+/// Collects the start of elementary streams, beginning with the first PES packet
+fn collect_stream_data<'a>(
+    packets: impl Iterator<Item = Packet<'a>>,
+    streams: &[Stream],
+) -> HashMap<u16, Vec<u8>> {
+    let mut data = HashMap::<u16, Vec<u8>>::new();
+
+    for packet in packets {
+        let Some(stream) = streams.iter().find(|s| s.pid == packet.pid) else {
+            continue;
+        };
+        let limit = match stream.kind() {
+            Some(Kind::Video(_)) => VIDEO_DATA_LEN,
+            Some(Kind::Audio(_)) => AUDIO_DATA_LEN,
+            _ => continue,
+        };
+
+        let payload = if packet.unit_start {
+            pes_payload(packet.payload)
+        } else if data.contains_key(&packet.pid) {
+            Some(packet.payload)
+        } else {
+            None
+        };
+
+        if let Some(payload) = payload {
+            let stream_data = data.entry(packet.pid).or_default();
+            if stream_data.len() < limit {
+                stream_data.extend_from_slice(payload);
+            }
+        }
+    }
+
+    data
+}
+
+/// Payload of PES packet without its header
+fn pes_payload(data: &[u8]) -> Option<&[u8]> {
+    if data.get(..3)? != [0, 0, 1] {
+        return None;
+    }
+
+    data.get(9 + usize::from(*data.get(8)?)..)
+}
+
+enum Kind {
+    Video(&'static str),
+    Audio(&'static str),
+    Subtitle(&'static str),
+}
+
+impl Stream {
+    fn kind(&self) -> Option<Kind> {
+        let kind = match self.stream_type {
+            0x01 => Kind::Video("MPEG-1"),
+            0x02 => Kind::Video("MPEG-2"),
+            0x10 => Kind::Video("MPEG-4 Visual"),
+            0x1B => Kind::Video("AVC / h264"),
+            0x24 => Kind::Video("HEVC / h265"),
+            0xEA => Kind::Video("VC-1"),
+            0x03 | 0x04 => Kind::Audio("MP2"),
+            0x0F | 0x11 => Kind::Audio("AAC"),
+            0x80 => Kind::Audio("PCM"),
+            0x81 => Kind::Audio("AC-3"),
+            0x82 | 0x85 | 0x86 | 0xA2 => Kind::Audio("DTS"),
+            0x83 => Kind::Audio("TrueHD"),
+            0x84 | 0x87 | 0xA1 => Kind::Audio("E-AC-3"),
+            0x90 => Kind::Subtitle("PGS"),
+            0x92 => Kind::Subtitle("TextST"),
+            // private data are described only by descriptors
+            0x06 => self.descriptors.iter().find_map(|(tag, body)| match *tag {
+                AC3_DESCRIPTOR => Some(Kind::Audio("AC-3")),
+                EAC3_DESCRIPTOR => Some(Kind::Audio("E-AC-3")),
+                DTS_DESCRIPTOR => Some(Kind::Audio("DTS")),
+                SUBTITLING_DESCRIPTOR => Some(Kind::Subtitle("DVB")),
+                TELETEXT_DESCRIPTOR => Some(Kind::Subtitle("Teletext")),
+                REGISTRATION_DESCRIPTOR => match body.as_slice() {
+                    b"AC-3" => Some(Kind::Audio("AC-3")),
+                    b"EAC3" => Some(Kind::Audio("E-AC-3")),
+                    b"Opus" => Some(Kind::Audio("Opus")),
+                    _ => None,
+                },
+                _ => None,
+            })?,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    fn descriptor(&self, tag: u8) -> Option<&[u8]> {
+        self.descriptors
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, body)| body.as_slice())
+    }
+
+    fn language(&self) -> Option<String> {
+        let code = self
+            .descriptor(ISO_639_LANGUAGE_DESCRIPTOR)
+            .or_else(|| self.descriptor(SUBTITLING_DESCRIPTOR))
+            .or_else(|| self.descriptor(TELETEXT_DESCRIPTOR))?
+            .get(..3)?;
+
+        Some(String::from_utf8_lossy(code).into_owned())
+            .filter(|l| l.chars().all(|c| c.is_ascii_alphabetic()) && l != "und")
+    }
+}
+
+fn video_track(stream: &Stream, codec: &str, data: &[u8]) -> VideoTrack {
+    let header = match stream.stream_type {
+        0x01 | 0x02 => mpeg2_sequence_header(data),
+        0x1B => nal_units(data)
+            .find(|nal| nal.first().is_some_and(|h| h & 0x1F == 7))
+            .and_then(|nal| avc_sps(&unescape(&nal[1..]))),
+        0x24 => nal_units(data)
+            .find(|nal| nal.first().is_some_and(|h| h >> 1 & 0x3F == 33))
+            .and_then(|nal| hevc_sps(&unescape(nal.get(2..)?))),
+        _ => None,
+    }
+    .unwrap_or_default();
+
+    VideoTrack {
+        codec: codec.into(),
+        width: header.width,
+        height: header.height,
+        frame_rate: header.frame_rate,
+        bit_depth: header.bit_depth,
+        colour: None,
+        hdr: stream
+            .descriptor(DOVI_VIDEO_DESCRIPTOR)
+            .map(|_| HdrFormat::DolbyVision),
+    }
+}
+
+fn audio_track(stream: &Stream, codec: &str, data: &[u8]) -> AudioTrack {
+    let (channels, sample_rate) = match codec {
+        "AC-3" | "E-AC-3" => ac3_header(data),
+        "AAC" => adts_header(data),
+        "MP2" => mpeg_audio_header(data),
+        "DTS" => dts_header(data),
+        "PCM" => lpcm_header(data),
+        _ => None,
+    }
+    .unwrap_or_default();
+
+    AudioTrack {
+        codec: codec.into(),
+        name: None,
+        channels,
+        sample_rate,
+        language: stream.language(),
+        default: false,
+        forced: false,
+    }
+}
+
+fn subtitle_track(stream: &Stream, codec: &str) -> SubtitleTrack {
+    // subtitling types of DVB subtitles for the hard of hearing
+    let hearing_impaired = stream
+        .descriptor(SUBTITLING_DESCRIPTOR)
+        .and_then(|d| d.get(3))
+        .is_some_and(|t| (0x20..=0x25).contains(t));
+
+    SubtitleTrack {
+        codec: codec.into(),
+        name: None,
+        language: stream.language(),
+        default: false,
+        forced: false,
+        hearing_impaired,
+    }
+}
+
+#[derive(Default)]
+struct VideoHeader {
+    width: u64,
+    height: u64,
+    frame_rate: Option<f64>,
+    bit_depth: Option<u64>,
+}
+
+fn mpeg2_sequence_header(data: &[u8]) -> Option<VideoHeader> {
+    let start = data.windows(4).position(|w| w == [0, 0, 1, 0xB3])?;
+    let header = data.get(start + 4..start + 8)?;
+
+    let frame_rate = match header[3] & 0x0F {
+        1 => Some(24_000.0 / 1001.0),
+        2 => Some(24.0),
+        3 => Some(25.0),
+        4 => Some(30_000.0 / 1001.0),
+        5 => Some(30.0),
+        6 => Some(50.0),
+        7 => Some(60_000.0 / 1001.0),
+        8 => Some(60.0),
+        _ => None,
+    };
+
+    Some(VideoHeader {
+        width: u64::from(header[0]) << 4 | u64::from(header[1] >> 4),
+        height: u64::from(header[1] & 0x0F) << 8 | u64::from(header[2]),
+        frame_rate,
+        bit_depth: Some(8),
+    })
+}
+
+/// Resolution and bit depth from H.264 sequence parameter set
+fn avc_sps(sps: &[u8]) -> Option<VideoHeader> {
+    let mut r = BitReader::new(sps);
+    let profile = r.bits(8)?;
+    r.skip(16)?;
+    r.ue()?;
+
+    let mut chroma_format = 1;
+    let mut bit_depth = 8;
+    if [100, 110, 122, 244, 44, 83, 86, 118, 128, 138, 139, 134, 135].contains(&profile) {
+        chroma_format = r.ue()?;
+        if chroma_format == 3 {
+            r.skip(1)?;
+        }
+        bit_depth += r.ue()?;
+        r.ue()?;
+        r.skip(1)?;
+
+        if r.bits(1)? == 1 {
+            let lists = if chroma_format == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if r.bits(1)? == 1 {
+                    skip_scaling_list(&mut r, if i < 6 { 16 } else { 64 })?;
+                }
+            }
+        }
+    }
+
+    r.ue()?;
+    match r.ue()? {
+        0 => {
+            r.ue()?;
+        }
+        1 => {
+            r.skip(1)?;
+            r.se()?;
+            r.se()?;
+            for _ in 0..r.ue()? {
+                r.se()?;
+            }
+        }
+        _ => {}
+    }
+    r.ue()?;
+    r.skip(1)?;
+
+    let width_in_macroblocks = r.ue()? + 1;
+    let height_in_map_units = r.ue()? + 1;
+    let frame_mbs_only = r.bits(1)?;
+    if frame_mbs_only == 0 {
+        r.skip(1)?;
+    }
+    r.skip(1)?;
+
+    let mut width = width_in_macroblocks * 16;
+    let mut height = (2 - frame_mbs_only) * height_in_map_units * 16;
+
+    if r.bits(1)? == 1 {
+        let (left, right, top, bottom) = (r.ue()?, r.ue()?, r.ue()?, r.ue()?);
+        let (crop_x, crop_y) = match chroma_format {
+            1 => (2, 2 * (2 - frame_mbs_only)),
+            2 => (2, 2 - frame_mbs_only),
+            _ => (1, 2 - frame_mbs_only),
+        };
+        width = width.checked_sub(crop_x * (left + right))?;
+        height = height.checked_sub(crop_y * (top + bottom))?;
+    }
+
+    Some(VideoHeader {
+        width,
+        height,
+        frame_rate: None,
+        bit_depth: Some(bit_depth),
+    })
+}
+
+fn skip_scaling_list(r: &mut BitReader, size: usize) -> Option<()> {
+    let mut last = 8;
+    let mut next = 8;
+
+    for _ in 0..size {
+        if next != 0 {
+            next = (last + r.se()? + 256) % 256;
+        }
+        if next != 0 {
+            last = next;
+        }
+    }
+
+    Some(())
+}
+
+/// Resolution and bit depth from H.265 sequence parameter set
+fn hevc_sps(sps: &[u8]) -> Option<VideoHeader> {
+    let mut r = BitReader::new(sps);
+    r.skip(4)?;
+    let sub_layers = r.bits(3)? as usize;
+    r.skip(1)?;
+
+    // profile, tier and level
+    r.skip(96)?;
+    let mut present = Vec::new();
+    for _ in 0..sub_layers {
+        present.push((r.bits(1)? == 1, r.bits(1)? == 1));
+    }
+    if sub_layers > 0 {
+        r.skip(2 * (8 - sub_layers))?;
+    }
+    for (profile, level) in present {
+        if profile {
+            r.skip(88)?;
+        }
+        if level {
+            r.skip(8)?;
+        }
+    }
+
+    r.ue()?;
+    let chroma_format = r.ue()?;
+    if chroma_format == 3 {
+        r.skip(1)?;
+    }
+    let mut width = r.ue()?;
+    let mut height = r.ue()?;
+
+    if r.bits(1)? == 1 {
+        let (left, right, top, bottom) = (r.ue()?, r.ue()?, r.ue()?, r.ue()?);
+        let crop_x = if matches!(chroma_format, 1 | 2) { 2 } else { 1 };
+        let crop_y = if chroma_format == 1 { 2 } else { 1 };
+        width = width.checked_sub(crop_x * (left + right))?;
+        height = height.checked_sub(crop_y * (top + bottom))?;
+    }
+
+    Some(VideoHeader {
+        width,
+        height,
+        frame_rate: None,
+        bit_depth: Some(r.ue()? + 8),
+    })
+}
+
+/// Channels and sample rate of AC-3 or E-AC-3 sync frame
+fn ac3_header(data: &[u8]) -> Option<(u64, f64)> {
+    let start = data.windows(2).position(|w| w == [0x0B, 0x77])?;
+    let mut r = BitReader::new(data.get(start + 2..)?);
+
+    let bsid = u64::from(data.get(start + 5)? >> 3);
+    if bsid > 10 {
+        // E-AC-3: stream type, substream id and frame size
+        r.skip(16)?;
+        let sample_rate = match r.bits(2)? {
+            3 => [24_000.0, 22_050.0, 16_000.0]
+                .get(r.bits(2)? as usize)
+                .copied()?,
+            code => {
+                r.skip(2)?;
+                [48_000.0, 44_100.0, 32_000.0][code as usize]
+            }
+        };
+        let acmod = r.bits(3)?;
+        let lfe = r.bits(1)?;
+
+        return Some((acmod_channels(acmod) + lfe, sample_rate));
+    }
+
+    // CRC, then sample rate and frame size code
+    r.skip(16)?;
+    let sample_rate = [48_000.0, 44_100.0, 32_000.0]
+        .get(r.bits(2)? as usize)
+        .copied()?;
+    r.skip(6 + 8)?;
+    let acmod = r.bits(3)?;
+    if acmod & 0x01 != 0 && acmod != 1 {
+        r.skip(2)?;
+    }
+    if acmod & 0x04 != 0 {
+        r.skip(2)?;
+    }
+    if acmod == 2 {
+        r.skip(2)?;
+    }
+    let lfe = r.bits(1)?;
+
+    Some((acmod_channels(acmod) + lfe, sample_rate))
+}
+
+fn acmod_channels(acmod: u64) -> u64 {
+    [2, 1, 2, 3, 3, 4, 4, 5][acmod as usize & 0x07]
+}
+
+/// Channels and sample rate of AAC frame with ADTS header
+fn adts_header(data: &[u8]) -> Option<(u64, f64)> {
+    let start = data
+        .windows(2)
+        .position(|w| w[0] == 0xFF && w[1] & 0xF6 == 0xF0)?;
+    let header = data.get(start..start + 4)?;
+
+    let sample_rate = [
+        96_000.0, 88_200.0, 64_000.0, 48_000.0, 44_100.0, 32_000.0, 24_000.0, 22_050.0, 16_000.0,
+        12_000.0, 11_025.0, 8_000.0, 7_350.0,
+    ]
+    .get(usize::from(header[2] >> 2 & 0x0F))
+    .copied()?;
+    let channels = match (header[2] & 0x01) << 2 | header[3] >> 6 {
+        7 => 8,
+        channels => u64::from(channels),
+    };
+
+    Some((channels, sample_rate))
+}
+
+/// Channels and sample rate of MPEG audio frame
+fn mpeg_audio_header(data: &[u8]) -> Option<(u64, f64)> {
+    let start = data
+        .windows(2)
+        .position(|w| w[0] == 0xFF && w[1] & 0xE0 == 0xE0 && w[1] & 0x06 != 0)?;
+    let header = data.get(start..start + 4)?;
+
+    let divisor = match header[1] >> 3 & 0x03 {
+        3 => 1.0,
+        2 => 2.0,
+        0 => 4.0,
+        _ => return None,
+    };
+    let sample_rate = [44_100.0, 48_000.0, 32_000.0]
+        .get(usize::from(header[2] >> 2 & 0x03))
+        .copied()?;
+    let channels = if header[3] >> 6 == 3 { 1 } else { 2 };
+
+    Some((channels, sample_rate / divisor))
+}
+
+/// Channels and sample rate of DTS core frame
+fn dts_header(data: &[u8]) -> Option<(u64, f64)> {
+    let start = data
+        .windows(4)
+        .position(|w| w == [0x7F, 0xFE, 0x80, 0x01])?;
+    let mut r = BitReader::new(data.get(start + 4..)?);
+
+    r.skip(28)?;
+    let channels = [1, 2, 2, 2, 2, 3, 3, 4, 4, 5, 6, 6, 6, 7, 8, 8]
+        .get(r.bits(6)? as usize)
+        .copied()?;
+    let sample_rate = match r.bits(4)? {
+        1 => 8_000.0,
+        2 => 16_000.0,
+        3 => 32_000.0,
+        6 => 11_025.0,
+        7 => 22_050.0,
+        8 => 44_100.0,
+        11 => 12_000.0,
+        12 => 24_000.0,
+        13 => 48_000.0,
+        _ => return None,
+    };
+    r.skip(15)?;
+    let lfe = u64::from(r.bits(2)? != 0);
+
+    Some((channels + lfe, sample_rate))
+}
+
+/// Channels and sample rate of Blu-ray LPCM, from header of each PES payload
+fn lpcm_header(data: &[u8]) -> Option<(u64, f64)> {
+    let header = data.get(2)?;
+
+    let channels = [0, 1, 0, 2, 3, 3, 4, 4, 5, 6, 7, 8]
+        .get(usize::from(header >> 4))
+        .copied()
+        .filter(|c| *c != 0)?;
+    let sample_rate = match header & 0x0F {
+        1 => 48_000.0,
+        4 => 96_000.0,
+        5 => 192_000.0,
+        _ => return None,
+    };
+
+    Some((channels, sample_rate))
+}
+
+/// Iterates over NAL units separated by start codes
+fn nal_units(data: &[u8]) -> NalUnits<'_> {
+    NalUnits { data }
+}
+
+struct NalUnits<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for NalUnits<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.data.windows(3).position(|w| w == [0, 0, 1])? + 3;
+        let rest = &self.data[start..];
+        let end = rest
+            .windows(3)
+            .position(|w| w == [0, 0, 1])
+            .unwrap_or(rest.len());
+
+        self.data = &rest[end..];
+
+        Some(&rest[..end])
+    }
+}
+
+/// Removes emulation prevention bytes
+fn unescape(nal: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(nal.len());
+    let mut zeros = 0;
+
+    for byte in nal {
+        if zeros >= 2 && *byte == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if *byte == 0 { zeros + 1 } else { 0 };
+        data.push(*byte);
+    }
+
+    data
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bits(&mut self, count: u32) -> Option<u64> {
+        let mut value = 0;
+
+        for _ in 0..count {
+            let byte = self.data.get(self.position / 8)?;
+            let bit = byte >> (7 - self.position % 8) & 0x01;
+            value = value << 1 | u64::from(bit);
+            self.position += 1;
+        }
+
+        Some(value)
+    }
+
+    fn skip(&mut self, count: usize) -> Option<()> {
+        self.position += count;
+
+        (self.position <= self.data.len() * 8).then_some(())
+    }
+
+    /// Unsigned Exp-Golomb code
+    fn ue(&mut self) -> Option<u64> {
+        let mut zeros = 0;
+        while self.bits(1)? == 0 {
+            zeros += 1;
+            if zeros > 32 {
+                return None;
+            }
+        }
+
+        Some((1 << zeros) - 1 + self.bits(zeros)?)
+    }
+
+    /// Signed Exp-Golomb code
+    fn se(&mut self) -> Option<i64> {
+        let value = self.ue()? as i64;
+
+        Some(if value % 2 == 1 {
+            (value + 1) / 2
+        } else {
+            -(value / 2)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::metadata::tests::write_file;
+
+    struct BitWriter {
+        data: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        fn bits(&mut self, count: u32, value: u64) -> &mut Self {
+            for i in (0..count).rev() {
+                if self.bits.is_multiple_of(8) {
+                    self.data.push(0);
+                }
+                *self.data.last_mut().unwrap() |= ((value >> i & 1) as u8) << (7 - self.bits % 8);
+                self.bits += 1;
+            }
+
+            self
+        }
+
+        fn ue(&mut self, value: u64) -> &mut Self {
+            let zeros = 63 - (value + 1).leading_zeros();
+            self.bits(zeros, 0).bits(zeros + 1, value + 1)
+        }
+    }
+
+    /// H.264 High profile SPS of 1920x1080 video
+    fn sps() -> Vec<u8> {
+        let mut w = BitWriter {
+            data: Vec::new(),
+            bits: 0,
+        };
+        w.bits(8, 100).bits(8, 0).bits(8, 40).ue(0);
+        // 4:2:0, 8-bit, no scaling matrices
+        w.ue(1).ue(0).ue(0).bits(1, 0).bits(1, 0);
+        // frame numbers and picture order
+        w.ue(0).ue(0).ue(0).ue(4).bits(1, 0);
+        // 120x68 macroblocks, progressive, cropped by 8 lines
+        w.ue(119).ue(67).bits(1, 1).bits(1, 1).bits(1, 1);
+        w.ue(0).ue(0).ue(0).ue(4).bits(1, 0).bits(1, 1);
+
+        [&[0, 0, 0, 1, 0x67][..], &w.data].concat()
+    }
+
+    fn packet(pid: u16, unit_start: bool, pcr: Option<u64>, payload: &[u8]) -> Vec<u8> {
+        let stuffing = 184 - payload.len();
+        let adaptation = match (stuffing, pcr) {
+            (0, None) => Vec::new(),
+            (1, None) => vec![0],
+            (_, pcr) => {
+                let mut field = vec![stuffing as u8 - 1, if pcr.is_some() { 0x10 } else { 0 }];
+                if let Some(pcr) = pcr {
+                    field.extend_from_slice(&((pcr << 15) | 0x7E00).to_be_bytes()[2..]);
+                }
+                field.resize(stuffing, 0xFF);
+                field
+            }
+        };
+        let control = if adaptation.is_empty() { 0x10 } else { 0x30 };
+
+        [
+            &[
+                SYNC_BYTE,
+                (unit_start as u8) << 6 | (pid >> 8) as u8,
+                pid as u8,
+                control,
+            ][..],
+            &adaptation,
+            payload,
+        ]
+        .concat()
+    }
+
+    fn section(table_id: u8, body: &[u8]) -> Vec<u8> {
+        let len = body.len() as u16 + 9;
+        [
+            &[
+                0,
+                table_id,
+                0xB0 | (len >> 8) as u8,
+                len as u8,
+                0,
+                1,
+                0xC1,
+                0,
+                0,
+            ][..],
+            body,
+            &[0; 4],
+        ]
+        .concat()
+    }
+
+    fn pes(stream_id: u8, data: &[u8]) -> Vec<u8> {
+        [&[0, 0, 1, stream_id, 0, 0, 0x80, 0, 0][..], data].concat()
+    }
+
+    fn elementary_stream(stream_type: u8, pid: u16, descriptors: &[Vec<u8>]) -> Vec<u8> {
+        let descriptors = descriptors.concat();
+        [
+            &[stream_type, 0xE0 | (pid >> 8) as u8, pid as u8][..],
+            &(0xF000 | descriptors.len() as u16).to_be_bytes(),
+            &descriptors,
+        ]
+        .concat()
+    }
+
+    fn descriptor(tag: u8, body: &[u8]) -> Vec<u8> {
+        [&[tag, body.len() as u8][..], body].concat()
+    }
+
+    fn transport_stream() -> Vec<u8> {
+        let pat = section(0x00, &[0, 0, 0xE0, 0x10, 0, 1, 0xE1, 0x00]);
+        let pmt = section(
+            0x02,
+            &[
+                &[0xE1, 0x01, 0xF0, 0x00][..],
+                &elementary_stream(0x1B, 0x101, &[]),
+                &elementary_stream(0x81, 0x102, &[descriptor(0x0A, b"eng\x00")]),
+                &elementary_stream(0x0F, 0x103, &[descriptor(0x0A, b"fre\x00")]),
+                &elementary_stream(0x06, 0x104, &[descriptor(0x59, b"ger\x20\x00\x01\x00\x01")]),
+            ]
+            .concat(),
+        );
+
+        // AC-3 5.1 at 48 kHz and AAC stereo at 48 kHz
+        let ac3 = [0x0B, 0x77, 0, 0, 0x1C, 0x40, 0xE1, 0x40];
+        let adts = [0xFF, 0xF1, 0x4C, 0x80, 0, 0x1F, 0xFC];
+
+        let mut packets = vec![
+            packet(0x000, true, None, &pat),
+            packet(0x100, true, None, &pmt),
+            packet(0x101, true, Some(900), &pes(0xE0, &sps())),
+            packet(0x102, true, None, &pes(0xBD, &ac3)),
+            packet(0x103, true, None, &pes(0xC0, &adts)),
+        ];
+        packets.extend((0..20).map(|_| packet(0x1FFF, false, None, &[0xFF; 184])));
+        packets.push(packet(
+            0x101,
+            false,
+            Some(900 + 7200 * CLOCK_FREQUENCY),
+            &[],
+        ));
+
+        packets.concat()
+    }
+
+    fn assert_metadata(metadata: &MediaMetadata) {
+        assert_eq!(metadata.duration, Duration::from_secs(7200));
+
+        let video = &metadata.video;
+        assert_eq!(video.codec, "AVC / h264");
+        assert_eq!((video.width, video.height), (1920, 1080));
+        assert_eq!(video.bit_depth, Some(8));
+
+        assert_eq!(metadata.audio_tracks.len(), 2);
+        let ac3 = &metadata.audio_tracks[0];
+        assert_eq!(ac3.codec, "AC-3");
+        assert_eq!(ac3.layout(), "5.1");
+        assert_eq!(ac3.sample_rate, 48_000.0);
+        assert_eq!(ac3.language.as_deref(), Some("eng"));
+        let aac = &metadata.audio_tracks[1];
+        assert_eq!(aac.codec, "AAC");
+        assert_eq!(aac.layout(), "2.0");
+        assert_eq!(aac.sample_rate, 48_000.0);
+
+        assert_eq!(metadata.subtitle_tracks.len(), 1);
+        let subtitles = &metadata.subtitle_tracks[0];
+        assert_eq!(subtitles.codec, "DVB");
+        assert_eq!(subtitles.language.as_deref(), Some("ger"));
+        assert!(subtitles.hearing_impaired);
+    }
+
+    #[test]
+    fn it_reads_transport_stream() {
+        let bytes = transport_stream();
+        let path = write_file("movie.ts", &bytes);
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.file_size, bytes.len() as u64);
+        assert_metadata(&metadata);
+    }
+
+    #[test]
+    fn it_reads_blu_ray_transport_stream() {
+        let bytes = transport_stream()
+            .chunks(PACKET_LEN)
+            .flat_map(|p| [&[0; 4][..], p].concat())
+            .collect::<Vec<_>>();
+        let path = write_file("movie.m2ts", &bytes);
+
+        let metadata = MediaMetadata::from_file(File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_metadata(&metadata);
+    }
+}
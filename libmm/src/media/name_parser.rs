@@ -1,3 +1,9 @@
+use crate::media::ReleaseInfo;
+use std::fmt::{Display, Formatter};
+
+/// Longer episode range is surely not a multi-episode file
+const MAX_EPISODE_RANGE: u32 = 50;
+
 pub struct NameParser;
 
 impl NameParser {
@@ -5,16 +11,30 @@ impl NameParser {
     /// Expects filename without extension
     ///
     pub fn parse(filename: impl AsRef<str>) -> ParsedName {
-        let title = String::from(filename.as_ref());
+        let (title, plex_edition) = Self::take_plex_edition(filename.as_ref());
         let mut title = title.replace('.', " ");
 
         let year = Self::guess_year(&title);
+        let words = title.split_whitespace().collect::<Vec<_>>();
+        let (separator, release) = ReleaseInfo::parse(&words, year.map(|(i, _)| i));
 
-        if let Some(index) = year
-            .map(|(i, _)| i)
-            .or_else(|| Self::find_known_separator(&title))
-        {
-            // year or resolution is usually last relevant info
+        // single word editions are searched only after year or release info, they are common in titles
+        let (index, edition) = match year.map(|(i, _)| i).or(separator) {
+            Some(end) => {
+                let (start, mut editions) = Self::find_edition_before(&words, end);
+                let after = if year.is_some() { end + 1 } else { end };
+                editions.extend(Self::find_edition(&words, after).1);
+
+                (
+                    Some(start),
+                    (!editions.is_empty()).then(|| editions.join(" ")),
+                )
+            }
+            None => Self::find_edition(&words, 1),
+        };
+
+        if let Some(index) = index {
+            // year, edition or release info is usually last relevant info
             title = title
                 .split_whitespace()
                 .take(index)
@@ -24,7 +44,248 @@ impl NameParser {
 
         let year = year.map(|(_, year)| year);
 
-        ParsedName { title, year }
+        ParsedName {
+            title,
+            year,
+            edition: plex_edition.or(edition),
+            release,
+        }
+    }
+
+    /// Removes Plex style edition tag, like `{edition-Director's Cut}`
+    fn take_plex_edition(filename: &str) -> (String, Option<String>) {
+        const TAG: &str = "{edition-";
+
+        if let Some(start) = filename.find(TAG) {
+            if let Some(len) = filename[start..].find('}') {
+                let edition = filename[start + TAG.len()..start + len].trim();
+                let rest = format!("{}{}", &filename[..start], &filename[start + len + 1..]);
+
+                return (
+                    rest,
+                    Some(edition.into()).filter(|e: &String| !e.is_empty()),
+                );
+            }
+        }
+
+        (filename.into(), None)
+    }
+
+    /// Finds edition markers like `Director's Cut` or `Extended` in words from index `start`.
+    ///
+    /// Returns index of first word of edition and name of edition.
+    fn find_edition(words: &[&str], start: usize) -> (Option<usize>, Option<String>) {
+        let words = normalize_edition_words(words);
+
+        let mut first = None;
+        let mut editions = Vec::new();
+        let mut i = start;
+
+        while i < words.len() {
+            let next = words.get(i + 1).map(String::as_str);
+            let Some((edition, len)) = Self::edition_marker(&words[i], next) else {
+                i += 1;
+                continue;
+            };
+
+            if first.is_none() {
+                // `The Final Cut`
+                let starts_with_the = i > start && words[i - 1] == "the";
+                first = Some(if starts_with_the { i - 1 } else { i });
+            }
+
+            if !editions.contains(&edition) {
+                editions.push(edition);
+            }
+            i += len;
+        }
+
+        let edition = (!editions.is_empty()).then(|| editions.join(" "));
+
+        (first, edition)
+    }
+
+    /// Finds two word edition markers like `Directors Cut` right before index `end`.
+    ///
+    /// Returns index of first word of editions and their names, title keeps at least one word.
+    fn find_edition_before(words: &[&str], end: usize) -> (usize, Vec<String>) {
+        let words = normalize_edition_words(words);
+
+        let mut start = end;
+        let mut editions = Vec::new();
+
+        // `The Final Cut` is title, not edition
+        while start >= 3 && words[start - 3] != "the" {
+            match Self::edition_marker(&words[start - 2], Some(&words[start - 1])) {
+                Some((edition, 2)) => {
+                    editions.insert(0, edition.to_owned());
+                    start -= 2;
+                }
+                _ => break,
+            }
+        }
+
+        (start, editions)
+    }
+
+    /// Edition starting with lowercase `word` and how many words it has
+    fn edition_marker(word: &str, next: Option<&str>) -> Option<(&'static str, usize)> {
+        let is_cut_or_edition = matches!(next, Some("cut" | "edition" | "version"));
+
+        let marker = match (word, next) {
+            ("directors" | "dc", Some("cut")) => ("Director's Cut", 2),
+            ("final", Some("cut")) => ("Final Cut", 2),
+            ("special", Some("edition")) => ("Special Edition", 2),
+            ("ultimate", _) if is_cut_or_edition => ("Ultimate Edition", 2),
+            ("collectors", Some("edition")) => ("Collector's Edition", 2),
+            ("extended", _) => ("Extended", if is_cut_or_edition { 2 } else { 1 }),
+            ("theatrical", _) => ("Theatrical", if is_cut_or_edition { 2 } else { 1 }),
+            ("unrated", _) => ("Unrated", if is_cut_or_edition { 2 } else { 1 }),
+            ("uncut", _) => ("Uncut", 1),
+            ("remastered", _) => ("Remastered", 1),
+            ("imax", _) => ("IMAX", if is_cut_or_edition { 2 } else { 1 }),
+            _ => return None,
+        };
+
+        Some(marker)
+    }
+
+    ///
+    /// Parses names of TV show episodes, like `Show.Name.S02E05.720p`
+    ///
+    /// Recognises `S01E02`, `1x02`, multi-episode `S01E01E02` and `S01E01-E03`,
+    /// dates of daily shows like `2023.04.01` and absolute numbering like `Show - 123`.
+    ///
+    /// Expects filename without extension
+    ///
+    pub fn parse_episode(filename: impl AsRef<str>) -> Option<ParsedEpisode> {
+        let name = filename.as_ref().replace(['.', '_'], " ");
+        let name = Self::strip_leading_group(&name);
+        let words = name.split_whitespace().collect::<Vec<_>>();
+
+        for (i, word) in words.iter().enumerate() {
+            let number = Self::parse_season_episode(word)
+                .or_else(|| Self::parse_cross_format(word))
+                .or_else(|| Self::parse_date(&words[i..]))
+                .or_else(|| Self::parse_absolute(&words[i..]));
+
+            if let Some(number) = number {
+                // episode number is always after title
+                let mut title = &words[..i];
+                while let [rest @ .., "-"] = title {
+                    title = rest;
+                }
+                let title = title.join(" ");
+
+                return Some(ParsedEpisode { title, number });
+            }
+        }
+        None
+    }
+
+    /// Removes release group in brackets, like `[Group] Show - 01`
+    fn strip_leading_group(name: &str) -> &str {
+        let trimmed = name.trim_start();
+
+        if trimmed.starts_with('[') {
+            if let Some(end) = trimmed.find(']') {
+                return &trimmed[end + 1..];
+            }
+        }
+        trimmed
+    }
+
+    /// Parses `S01E02`, `S01E01E02` and `S01E01-E03` forms
+    fn parse_season_episode(word: &str) -> Option<EpisodeNumber> {
+        let word = word.to_ascii_lowercase();
+        let (season, episodes) = word.strip_prefix('s')?.split_once('e')?;
+
+        Some(EpisodeNumber::Seasonal {
+            season: season.parse().ok()?,
+            episodes: Self::parse_episode_list(episodes, 'e')?,
+        })
+    }
+
+    /// Parses `1x02` and `1x02-03` forms
+    fn parse_cross_format(word: &str) -> Option<EpisodeNumber> {
+        let word = word.to_ascii_lowercase();
+        let (season, episodes) = word.split_once('x')?;
+
+        // rules out resolutions like 1920x1080
+        if season.len() > 2 || episodes.len() > 7 {
+            return None;
+        }
+
+        Some(EpisodeNumber::Seasonal {
+            season: season.parse().ok()?,
+            episodes: Self::parse_episode_list(episodes, 'x')?,
+        })
+    }
+
+    /// Parses list of episodes like `01`, `01e02` or range `01-e03`
+    fn parse_episode_list(list: &str, separator: char) -> Option<Vec<u32>> {
+        if let Some((first, last)) = list.split_once('-') {
+            let first: u32 = first.parse().ok()?;
+            let last: u32 = last.trim_start_matches(separator).parse().ok()?;
+
+            if last < first || last - first >= MAX_EPISODE_RANGE {
+                return None;
+            }
+
+            Some((first..=last).collect())
+        } else {
+            list.split(separator).map(|n| n.parse().ok()).collect()
+        }
+    }
+
+    /// Parses air date of daily shows, either split to words or as `2023-04-01`
+    fn parse_date(words: &[&str]) -> Option<EpisodeNumber> {
+        let (year, month, day) = match words {
+            [date, ..] if date.len() == 10 && date.matches('-').count() == 2 => {
+                let mut parts = date.split('-');
+                (parts.next()?, parts.next()?, parts.next()?)
+            }
+            [year, month, day, ..] => (*year, *month, *day),
+            _ => return None,
+        };
+
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+
+        let year = year.parse().ok()?;
+        let month = month.parse().ok()?;
+        let day = day.parse().ok()?;
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        Some(EpisodeNumber::Date { year, month, day })
+    }
+
+    /// Parses absolute numbering used mostly by anime, like `Show - 123`, `Show - 05v2` or `Show E123`
+    fn parse_absolute(words: &[&str]) -> Option<EpisodeNumber> {
+        let number = match words {
+            ["-", number, ..] => number.split_once('v').map_or(*number, |(n, _)| n),
+            [word, ..] => {
+                let word = word.to_ascii_lowercase();
+                let number = word.strip_prefix("ep").or_else(|| word.strip_prefix('e'))?;
+
+                if !number.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+
+                return number.parse().ok().map(EpisodeNumber::Absolute);
+            }
+            _ => return None,
+        };
+
+        if number.len() > 4 {
+            return None;
+        }
+
+        number.parse().ok().map(EpisodeNumber::Absolute)
     }
 
     fn guess_year(title: &str) -> Option<(usize, usize)> {
@@ -41,22 +302,57 @@ impl NameParser {
         }
         None
     }
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ParsedName {
+    pub title: String,
+    pub year: Option<usize>,
+    /// Name of edition or cut, like `Director's Cut`
+    pub edition: Option<String>,
+    pub release: ReleaseInfo,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParsedEpisode {
+    pub title: String,
+    pub number: EpisodeNumber,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum EpisodeNumber {
+    /// One or more episodes of a season
+    Seasonal { season: u32, episodes: Vec<u32> },
+    /// Air date of daily shows
+    Date { year: u32, month: u32, day: u32 },
+    /// Number of episode counted across all seasons
+    Absolute(u32),
+}
 
-    fn find_known_separator(title: &str) -> Option<usize> {
-        for (i, word) in title.split_whitespace().enumerate() {
-            match word {
-                "720p" | "1080p" | "2160p" => return Some(i),
-                _ => continue,
+impl Display for EpisodeNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Seasonal { season, episodes } => {
+                f.write_fmt(format_args!("S{season:02}"))?;
+                for episode in episodes {
+                    f.write_fmt(format_args!("E{episode:02}"))?;
+                }
+                Ok(())
+            }
+            Self::Date { year, month, day } => {
+                f.write_fmt(format_args!("{year:04}-{month:02}-{day:02}"))
             }
+            Self::Absolute(number) => f.write_fmt(format_args!("#{number}")),
         }
-        None
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct ParsedName {
-    pub title: String,
-    pub year: Option<usize>,
+/// Lowercase words without apostrophes, like `directors` for `Director's`
+fn normalize_edition_words(words: &[&str]) -> Vec<String> {
+    words
+        .iter()
+        .map(|w| w.to_lowercase().replace(['\'', '’'], ""))
+        .collect()
 }
 
 #[cfg(test)]
@@ -75,14 +371,140 @@ mod tests {
             ParsedName {
                 title: "Movie Name".into(),
                 year: Some(1929),
+                edition: None,
+                release: ReleaseInfo {
+                    source: Some("DVDRip".into()),
+                    ..Default::default()
+                },
             },
             ParsedName {
                 title: "Another Movie 5".into(),
                 year: Some(2015),
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("1080p".into()),
+                    source: Some("BluRay".into()),
+                    video_codec: Some("H.264".into()),
+                    group: Some("Some".into()),
+                    ..Default::default()
+                },
             },
             ParsedName {
                 title: "Awesome Movie".into(),
                 year: None,
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("720p".into()),
+                    ..Default::default()
+                },
+            },
+        ];
+
+        for (name, expected) in names.iter().zip(expected) {
+            assert_eq!(NameParser::parse(name), expected);
+        }
+    }
+
+    #[test]
+    pub fn it_parses_release_info() {
+        let names = [
+            "Movie.Name.2019.2160p.UHD.BluRay.REMUX.HDR10.DV.HEVC.TrueHD.7.1.Atmos-GROUP",
+            "Movie Name 2020 MULTi 1080p WEB-DL H.264 DDP5.1-Other[rarbg]",
+            "Movie.Name.720p.WEBRip.x265.AAC2.0.ENG.FRENCH",
+            "The.English.Patient.DVDRip.XviD",
+            "Spider-Man",
+            "Charlottes.Web",
+            "HDR.Movie.DV.1080p.WEB",
+            "Movie.Name.2019.1080p.WEB-DL.DD5.1.H.264-GROUP",
+        ];
+
+        let expected = [
+            ParsedName {
+                title: "Movie Name".into(),
+                year: Some(2019),
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("2160p".into()),
+                    source: Some("BluRay".into()),
+                    remux: true,
+                    video_codec: Some("H.265".into()),
+                    audio: vec!["TrueHD".into(), "Atmos".into()],
+                    hdr: vec!["HDR10".into(), "Dolby Vision".into()],
+                    group: Some("GROUP".into()),
+                    languages: vec![],
+                },
+            },
+            ParsedName {
+                title: "Movie Name".into(),
+                year: Some(2020),
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("1080p".into()),
+                    source: Some("WEB-DL".into()),
+                    video_codec: Some("H.264".into()),
+                    audio: vec!["EAC3".into()],
+                    group: Some("Other".into()),
+                    languages: vec!["Multi".into()],
+                    ..Default::default()
+                },
+            },
+            ParsedName {
+                title: "Movie Name".into(),
+                year: None,
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("720p".into()),
+                    source: Some("WEBRip".into()),
+                    video_codec: Some("H.265".into()),
+                    audio: vec!["AAC".into()],
+                    languages: vec!["English".into(), "French".into()],
+                    ..Default::default()
+                },
+            },
+            ParsedName {
+                title: "The English Patient".into(),
+                year: None,
+                edition: None,
+                release: ReleaseInfo {
+                    source: Some("DVDRip".into()),
+                    video_codec: Some("XviD".into()),
+                    ..Default::default()
+                },
+            },
+            ParsedName {
+                title: "Spider-Man".into(),
+                year: None,
+                edition: None,
+                release: ReleaseInfo::default(),
+            },
+            ParsedName {
+                title: "Charlottes Web".into(),
+                year: None,
+                edition: None,
+                release: ReleaseInfo::default(),
+            },
+            ParsedName {
+                title: "HDR Movie DV".into(),
+                year: None,
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("1080p".into()),
+                    source: Some("WEB".into()),
+                    ..Default::default()
+                },
+            },
+            ParsedName {
+                title: "Movie Name".into(),
+                year: Some(2019),
+                edition: None,
+                release: ReleaseInfo {
+                    resolution: Some("1080p".into()),
+                    source: Some("WEB-DL".into()),
+                    video_codec: Some("H.264".into()),
+                    audio: vec!["AC3".into()],
+                    group: Some("GROUP".into()),
+                    ..Default::default()
+                },
             },
         ];
 
@@ -90,4 +512,187 @@ mod tests {
             assert_eq!(NameParser::parse(name), expected);
         }
     }
+
+    #[test]
+    pub fn it_parses_editions() {
+        let names = [
+            "Blade.Runner.1982.The.Final.Cut.1080p.BluRay",
+            "Movie Name (2001) {edition-Director's Cut} 1080p",
+            "Movie.Name.Extended.Edition.720p",
+            "Movie.Name.2009.UNRATED.Remastered.BluRay",
+            "Movie.Name.Directors.Cut.2019",
+            "Movie.Name.2014.IMAX.2160p",
+            "Aliens.Special.Edition.1986.1080p",
+            "Movie Name Directors Cut (2019)",
+            "Movie.Name.720p.Extended.Edition",
+            "Movie.Name.2019.Directors.Cut",
+            "Movie.Name.Directors.Cut",
+            "Uncut.Gems.2019.1080p",
+            "The.Final.Cut.2004",
+            "Extended.Family.2020",
+            "Remastered.2019",
+        ];
+
+        let expected = [
+            ("Blade Runner", Some(1982), Some("Final Cut")),
+            ("Movie Name", Some(2001), Some("Director's Cut")),
+            ("Movie Name", None, Some("Extended")),
+            ("Movie Name", Some(2009), Some("Unrated Remastered")),
+            ("Movie Name", Some(2019), Some("Director's Cut")),
+            ("Movie Name", Some(2014), Some("IMAX")),
+            ("Aliens", Some(1986), Some("Special Edition")),
+            ("Movie Name", Some(2019), Some("Director's Cut")),
+            ("Movie Name", None, Some("Extended")),
+            ("Movie Name", Some(2019), Some("Director's Cut")),
+            ("Movie Name", None, Some("Director's Cut")),
+            ("Uncut Gems", Some(2019), None),
+            ("The Final Cut", Some(2004), None),
+            ("Extended Family", Some(2020), None),
+            ("Remastered", Some(2019), None),
+        ];
+
+        for (name, (title, year, edition)) in names.iter().zip(expected) {
+            let parsed = NameParser::parse(name);
+
+            assert_eq!(parsed.title, title, "{name}");
+            assert_eq!(parsed.year, year, "{name}");
+            assert_eq!(parsed.edition.as_deref(), edition, "{name}");
+        }
+    }
+
+    fn seasonal(title: &str, season: u32, episodes: &[u32]) -> ParsedEpisode {
+        ParsedEpisode {
+            title: title.into(),
+            number: EpisodeNumber::Seasonal {
+                season,
+                episodes: episodes.to_vec(),
+            },
+        }
+    }
+
+    #[test]
+    pub fn it_parses_episode_names() {
+        let names = [
+            "Show.Name.S02E05.720p",
+            "Another Show s01e10 WEB-DL",
+            "Show.Name.2005.S10E01.1080p.BluRay.x264-GROUP",
+        ];
+
+        let expected = [
+            seasonal("Show Name", 2, &[5]),
+            seasonal("Another Show", 1, &[10]),
+            seasonal("Show Name 2005", 10, &[1]),
+        ];
+
+        for (name, expected) in names.iter().zip(expected) {
+            assert_eq!(NameParser::parse_episode(name), Some(expected));
+        }
+
+        assert_eq!(NameParser::parse_episode("Movie.Name.1929.DVDRip"), None);
+        assert_eq!(NameParser::parse_episode("Movie.Name.1920x1080"), None);
+    }
+
+    #[test]
+    pub fn it_parses_cross_format() {
+        let names = [
+            "Show.Name.1x02.HDTV",
+            "Show Name 12x101",
+            "Show_Name_3x04-05",
+        ];
+
+        let expected = [
+            seasonal("Show Name", 1, &[2]),
+            seasonal("Show Name", 12, &[101]),
+            seasonal("Show Name", 3, &[4, 5]),
+        ];
+
+        for (name, expected) in names.iter().zip(expected) {
+            assert_eq!(NameParser::parse_episode(name), Some(expected));
+        }
+    }
+
+    #[test]
+    pub fn it_parses_multi_episodes() {
+        let names = [
+            "Show.Name.S01E01E02.720p",
+            "Show.Name.S01E01-E03.1080p",
+            "Show Name - S02E09-10",
+        ];
+
+        let expected = [
+            seasonal("Show Name", 1, &[1, 2]),
+            seasonal("Show Name", 1, &[1, 2, 3]),
+            seasonal("Show Name", 2, &[9, 10]),
+        ];
+
+        for (name, expected) in names.iter().zip(expected) {
+            assert_eq!(NameParser::parse_episode(name), Some(expected));
+        }
+
+        assert_eq!(NameParser::parse_episode("Show.Name.S01E01-E3000000"), None);
+    }
+
+    #[test]
+    pub fn it_parses_daily_episodes() {
+        let names = [
+            "Daily.Show.2023.04.01.Guest.Name.720p",
+            "Daily Show 2023-04-01 1080p",
+        ];
+
+        let expected = [
+            ParsedEpisode {
+                title: "Daily Show".into(),
+                number: EpisodeNumber::Date {
+                    year: 2023,
+                    month: 4,
+                    day: 1,
+                },
+            },
+            ParsedEpisode {
+                title: "Daily Show".into(),
+                number: EpisodeNumber::Date {
+                    year: 2023,
+                    month: 4,
+                    day: 1,
+                },
+            },
+        ];
+
+        for (name, expected) in names.iter().zip(expected) {
+            assert_eq!(NameParser::parse_episode(name), Some(expected));
+        }
+    }
+
+    #[test]
+    pub fn it_parses_absolute_episodes() {
+        let names = [
+            "[SubsPlease] One Piece - 1071 (1080p) [ABCD1234]",
+            "[Some Group] Anime Show - 05v2 [720p]",
+            "Anime.Show.E123.1080p",
+            "Anime_Show_-_12",
+        ];
+
+        let expected = [
+            ParsedEpisode {
+                title: "One Piece".into(),
+                number: EpisodeNumber::Absolute(1071),
+            },
+            ParsedEpisode {
+                title: "Anime Show".into(),
+                number: EpisodeNumber::Absolute(5),
+            },
+            ParsedEpisode {
+                title: "Anime Show".into(),
+                number: EpisodeNumber::Absolute(123),
+            },
+            ParsedEpisode {
+                title: "Anime Show".into(),
+                number: EpisodeNumber::Absolute(12),
+            },
+        ];
+
+        for (name, expected) in names.iter().zip(expected) {
+            assert_eq!(NameParser::parse_episode(name), Some(expected));
+        }
+    }
 }
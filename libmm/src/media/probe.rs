@@ -0,0 +1,89 @@
+//! Selection of container format reader by the file header
+
+use crate::error::{Error, MediaError};
+use crate::media::{AviProbe, MatroskaProbe, MediaMetadata, Mp4Probe, MpegTsProbe};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Length of header passed to [`MediaProbe::matches`], enough for several MPEG-TS packets
+pub(crate) const HEADER_LEN: usize = 1024;
+
+/// All supported container formats
+const PROBES: &[&dyn MediaProbe] = &[&MatroskaProbe, &Mp4Probe, &AviProbe, &MpegTsProbe];
+
+/// Reader of metadata from single container format
+pub trait MediaProbe {
+    /// Name of container format
+    fn name(&self) -> &'static str;
+
+    /// Whether file starting with `header` is in this container format, header may be shorter
+    /// than [`HEADER_LEN`] for small files
+    fn matches(&self, header: &[u8]) -> bool;
+
+    /// Reads metadata of file, which is positioned at its start
+    fn probe(&self, file: &mut File) -> Result<MediaMetadata, Error>;
+}
+
+/// Chooses probe by magic bytes in file header, file extension is not used
+pub(crate) fn find_probe(file: &mut File) -> Result<&'static dyn MediaProbe, Error> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    file.seek(SeekFrom::Start(0)).map_err(MediaError::Io)?;
+    file.take(HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .map_err(MediaError::Io)?;
+    file.seek(SeekFrom::Start(0)).map_err(MediaError::Io)?;
+
+    PROBES
+        .iter()
+        .find(|p| p.matches(&header))
+        .copied()
+        .ok_or(MediaError::UnsupportedContainer.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::metadata::tests::write_file;
+
+    fn probe_name(name: &str, bytes: &[u8]) -> Option<&'static str> {
+        let path = write_file(name, bytes);
+        let probe = find_probe(&mut File::open(&path).unwrap());
+        std::fs::remove_file(path).unwrap();
+
+        probe.ok().map(|p| p.name())
+    }
+
+    #[test]
+    fn it_detects_container_by_magic_bytes() {
+        let packets = |len: usize, offset: usize| {
+            (0..4)
+                .flat_map(|_| {
+                    let mut packet = vec![0xFF; len];
+                    packet[offset] = 0x47;
+                    packet
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // extension does not matter
+        assert_eq!(
+            probe_name("mkv.mp4", &[0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x86, 0x81]),
+            Some("Matroska")
+        );
+        assert_eq!(
+            probe_name("mp4.mkv", b"\x00\x00\x00\x20ftypisom"),
+            Some("MP4")
+        );
+        assert_eq!(probe_name("mov.mkv", b"\x00\x00\x00\x08wide"), Some("MP4"));
+        assert_eq!(
+            probe_name("avi.mkv", b"RIFF\x00\x10\x00\x00AVI LIST"),
+            Some("AVI")
+        );
+        assert_eq!(probe_name("ts.mkv", &packets(188, 0)), Some("MPEG-TS"));
+        assert_eq!(probe_name("m2ts.mkv", &packets(192, 4)), Some("MPEG-TS"));
+
+        assert_eq!(probe_name("wav.mkv", b"RIFF\x00\x10\x00\x00WAVEfmt "), None);
+        assert_eq!(probe_name("text.mkv", b"G not a transport stream"), None);
+        assert_eq!(probe_name("empty.mkv", b""), None);
+    }
+}
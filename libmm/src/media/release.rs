@@ -0,0 +1,227 @@
+use std::fmt::{Display, Formatter};
+
+/// Information about release parsed from filename, like `1080p.BluRay.x264-GROUP`
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ReleaseInfo {
+    pub resolution: Option<String>,
+    pub source: Option<String>,
+    pub remux: bool,
+    pub video_codec: Option<String>,
+    pub audio: Vec<String>,
+    pub hdr: Vec<String>,
+    pub group: Option<String>,
+    pub languages: Vec<String>,
+}
+
+impl ReleaseInfo {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Finds release tokens in words of filename.
+    ///
+    /// Returns index of first found token, all words before it are usually part of title.
+    /// Words after `year_index` are never considered part of title, ambiguous words like `web`
+    /// are considered tokens only after the year or another token.
+    pub(crate) fn parse(words: &[&str], year_index: Option<usize>) -> (Option<usize>, Self) {
+        let mut info = Self::default();
+        let mut first_token = None;
+
+        for (i, word) in words.iter().enumerate() {
+            let mut word = *word;
+
+            // release group is appended to last word, like `x264-GROUP`
+            if i == words.len() - 1 && Token::parse(word).is_none() {
+                if let Some((rest, group)) = word.rsplit_once('-') {
+                    let group = strip_brackets(group);
+                    let after_token = first_token.is_some()
+                        || year_index.is_some_and(|y| i > y)
+                        || (Token::parse(rest).is_some() && !is_ambiguous(rest));
+
+                    if after_token && !group.is_empty() {
+                        info.group = Some(group.into());
+                        word = rest;
+                    }
+                }
+            }
+
+            let mut tokens = Vec::new();
+            let mut ambiguous = false;
+            if let Some(token) = Token::parse(word) {
+                ambiguous = is_ambiguous(word);
+                tokens.push(token);
+            } else if word.eq_ignore_ascii_case("h") {
+                // `H.264` is split to two words, the last one can have release group appended
+                if let Some(token) = words
+                    .get(i + 1)
+                    .map(|n| n.split_once('-').map_or(*n, |(codec, _)| codec))
+                    .and_then(|n| Token::parse(&format!("h{n}")))
+                {
+                    tokens.push(token);
+                }
+            } else {
+                // compound words like `1080p-BluRay`
+                tokens.extend(word.split('-').filter_map(Token::parse));
+            }
+
+            for token in tokens {
+                // languages are common words, which can be part of title
+                let is_title_word = ambiguous || matches!(token, Token::Language(_));
+                if first_token.is_none() && !is_title_word {
+                    first_token = Some(i);
+                }
+
+                if first_token.is_some() || year_index.is_some_and(|y| i > y) {
+                    info.add(token);
+                }
+            }
+        }
+
+        (first_token, info)
+    }
+
+    fn add(&mut self, token: Token) {
+        match token {
+            Token::Resolution(r) => self.resolution = Some(r.into()),
+            Token::Source(s) => self.source = Some(s.into()),
+            Token::Remux => self.remux = true,
+            Token::VideoCodec(c) => self.video_codec = Some(c.into()),
+            Token::Audio(a) => push_unique(&mut self.audio, a),
+            Token::Hdr(h) => push_unique(&mut self.hdr, h),
+            Token::Language(l) => push_unique(&mut self.languages, l),
+        }
+    }
+}
+
+impl Display for ReleaseInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let remux = self.remux.then_some("Remux");
+
+        let parts = self
+            .resolution
+            .as_deref()
+            .into_iter()
+            .chain(self.source.as_deref())
+            .chain(remux)
+            .chain(self.hdr.iter().map(String::as_str))
+            .chain(self.video_codec.as_deref())
+            .chain(self.audio.iter().map(String::as_str))
+            .chain(self.languages.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+
+        f.write_str(&parts.join(" "))?;
+
+        if let Some(group) = &self.group {
+            f.write_fmt(format_args!("-{group}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn push_unique(vec: &mut Vec<String>, value: &str) {
+    if !vec.iter().any(|v| v == value) {
+        vec.push(value.into());
+    }
+}
+
+fn strip_brackets(word: &str) -> &str {
+    match word.find(['[', '(']) {
+        Some(i) => &word[..i],
+        None => word,
+    }
+}
+
+/// Checks for tokens, which are also common words or abbreviations in titles
+fn is_ambiguous(word: &str) -> bool {
+    let word = word.trim_matches(['(', ')', '[', ']']).to_ascii_lowercase();
+
+    matches!(
+        word.as_str(),
+        "4k" | "uhd" | "web" | "bd" | "hdr" | "dv" | "dovi"
+    )
+}
+
+/// Checks for audio format followed by optional channel count, like `aac2`
+fn has_channels(word: &str, format: &str) -> bool {
+    match word.strip_prefix(format) {
+        Some(channels) => channels.chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+enum Token {
+    Resolution(&'static str),
+    Source(&'static str),
+    Remux,
+    VideoCodec(&'static str),
+    Audio(&'static str),
+    Hdr(&'static str),
+    Language(&'static str),
+}
+
+impl Token {
+    fn parse(word: &str) -> Option<Self> {
+        let word = word.trim_matches(['(', ')', '[', ']']).to_ascii_lowercase();
+
+        let token = match word.as_str() {
+            "480p" => Self::Resolution("480p"),
+            "576p" => Self::Resolution("576p"),
+            "720p" => Self::Resolution("720p"),
+            "1080p" => Self::Resolution("1080p"),
+            "1080i" => Self::Resolution("1080i"),
+            "2160p" | "4k" | "uhd" => Self::Resolution("2160p"),
+
+            "bluray" | "blu-ray" | "bdrip" | "brrip" | "bd" => Self::Source("BluRay"),
+            "web-dl" | "webdl" => Self::Source("WEB-DL"),
+            "webrip" => Self::Source("WEBRip"),
+            "web" => Self::Source("WEB"),
+            "dvdrip" => Self::Source("DVDRip"),
+            "dvd" | "dvd5" | "dvd9" => Self::Source("DVD"),
+            "hdtv" => Self::Source("HDTV"),
+            "hdrip" => Self::Source("HDRip"),
+            "remux" => Self::Remux,
+
+            "x264" | "h264" | "avc" => Self::VideoCodec("H.264"),
+            "x265" | "h265" | "hevc" => Self::VideoCodec("H.265"),
+            "av1" => Self::VideoCodec("AV1"),
+            "vp9" => Self::VideoCodec("VP9"),
+            "xvid" | "divx" => Self::VideoCodec("XviD"),
+
+            "dts" => Self::Audio("DTS"),
+            "dts-hd" | "dtshd" => Self::Audio("DTS-HD"),
+            "dts-x" | "dtsx" => Self::Audio("DTS:X"),
+            "truehd" => Self::Audio("TrueHD"),
+            "atmos" => Self::Audio("Atmos"),
+            "flac" => Self::Audio("FLAC"),
+            "opus" => Self::Audio("Opus"),
+            "mp3" => Self::Audio("MP3"),
+            "ac3" => Self::Audio("AC3"),
+            "eac3" => Self::Audio("EAC3"),
+
+            "hdr" => Self::Hdr("HDR"),
+            "hdr10" => Self::Hdr("HDR10"),
+            "hdr10+" | "hdr10plus" => Self::Hdr("HDR10+"),
+            "dv" | "dovi" => Self::Hdr("Dolby Vision"),
+            "hlg" => Self::Hdr("HLG"),
+
+            "multi" => Self::Language("Multi"),
+            "eng" | "english" => Self::Language("English"),
+            "fre" | "french" | "vff" | "truefrench" => Self::Language("French"),
+            "ger" | "german" => Self::Language("German"),
+            "ita" | "italian" => Self::Language("Italian"),
+            "spa" | "spanish" => Self::Language("Spanish"),
+            "cz" | "cze" | "czech" => Self::Language("Czech"),
+            "jpn" | "japanese" => Self::Language("Japanese"),
+            "rus" | "russian" => Self::Language("Russian"),
+
+            // audio formats are often followed by channel count, like `AAC2.0` or `DDP5.1`
+            w if has_channels(w, "aac") => Self::Audio("AAC"),
+            w if has_channels(w, "ddp") || has_channels(w, "dd+") => Self::Audio("EAC3"),
+            w if has_channels(w, "dd") => Self::Audio("AC3"),
+            _ => return None,
+        };
+
+        Some(token)
+    }
+}
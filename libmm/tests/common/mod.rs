@@ -0,0 +1,144 @@
+//! Local stand-in for HTTP APIs, serving recorded responses
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Response served for requests with given path, query is ignored when matching
+pub struct Route {
+    path: String,
+    status: u16,
+    body: String,
+    headers: Vec<(String, String)>,
+    /// How many more times is route served, `None` for unlimited
+    remaining: Option<usize>,
+}
+
+impl Route {
+    pub fn new(path: impl Into<String>, status: u16, body: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            status,
+            body: body.into(),
+            headers: Vec::new(),
+            remaining: None,
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Route is served only `n` times, then next route with the same path is used
+    pub fn times(mut self, n: usize) -> Self {
+        self.remaining = Some(n);
+        self
+    }
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    /// Starts serving on random local port, requests to unknown paths get 404 with empty body
+    pub fn start(mut routes: Vec<Route>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => handle(stream, &mut routes, &log),
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Paths with queries of all received requests, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Base URL of a port nothing listens on, for transport errors
+pub fn closed_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    format!("http://{addr}")
+}
+
+fn handle(stream: TcpStream, routes: &mut [Route], log: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // headers are not needed, only read to the empty line
+    let mut header = String::new();
+    while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+        header.clear();
+    }
+
+    let target = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    let path = target.split('?').next().unwrap_or_default();
+    log.lock().unwrap().push(target.clone());
+
+    let route = routes
+        .iter_mut()
+        .find(|r| r.path == path && r.remaining != Some(0));
+
+    let (status, body, headers) = match route {
+        Some(route) => {
+            route.remaining = route.remaining.map(|n| n - 1);
+
+            let headers = route
+                .headers
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}\r\n"))
+                .collect::<String>();
+
+            (route.status, route.body.as_str(), headers)
+        }
+        None => (404, "", String::new()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{headers}Connection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    );
+
+    let _ = (&stream).write_all(response.as_bytes());
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,352 @@
+mod common;
+
+use common::{closed_url, MockServer, Route};
+use libmm::api::{ExternalId, MetadataProvider, ResponseCache, RetryPolicy, TmdbClient};
+use libmm::db::artwork::ArtworkKind;
+use libmm::db::movie::IncompleteMovie;
+use libmm::error::{ApiError, Error};
+use std::time::Duration;
+
+const MOVIE: &str = include_str!("fixtures/tmdb/movie_603.json");
+const SEARCH: &str = include_str!("fixtures/tmdb/search_matrix.json");
+const FIND: &str = include_str!("fixtures/tmdb/find_tt0133093.json");
+const SEARCH_TV: &str = include_str!("fixtures/tmdb/search_tv_game_of_thrones.json");
+const TV: &str = include_str!("fixtures/tmdb/tv_1399.json");
+const TV_SEASON: &str = include_str!("fixtures/tmdb/tv_1399_season_1.json");
+const TV_EXTERNAL_IDS: &str = include_str!("fixtures/tmdb/tv_1399_external_ids.json");
+const MOVIE_CREDITS: &str = include_str!("fixtures/tmdb/movie_603_credits.json");
+const CONFIGURATION: &str = include_str!("fixtures/tmdb/configuration.json");
+const MOVIE_IMAGES: &str = include_str!("fixtures/tmdb/movie_603_images.json");
+const ERROR_401: &str = include_str!("fixtures/tmdb/error_401.json");
+const ERROR_404: &str = include_str!("fixtures/tmdb/error_404.json");
+const ERROR_500: &str = include_str!("fixtures/tmdb/error_500.json");
+
+const FAST_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: 2,
+    base_delay: Duration::from_millis(1),
+    max_delay: Duration::from_millis(10),
+};
+
+fn client(server: &MockServer) -> TmdbClient {
+    TmdbClient::new("token".into())
+        .with_base_url(server.url())
+        .with_retry_policy(FAST_RETRY)
+}
+
+#[test]
+fn it_gets_movie_detail() {
+    let server = MockServer::start(vec![Route::new("/movie/603", 200, MOVIE)]);
+
+    let movie = client(&server).get_movie_detail(603).unwrap().unwrap();
+
+    assert_eq!(movie.tmdb_id, Some(603));
+    assert_eq!(movie.title, "The Matrix");
+    assert_eq!(movie.release_year, 1999);
+    assert_eq!(movie.runtime(), Some(136));
+    assert_eq!(movie.original_language, Some("en".into()));
+    assert_eq!(movie.tagline, Some("Welcome to the Real World.".into()));
+    assert_eq!(movie.imdb_id, Some("tt0133093".into()));
+    assert_eq!(movie.vote_average, Some(8.2));
+    assert_eq!(movie.genres.len(), 2);
+    assert_eq!(movie.genres[1].name, "Science Fiction");
+    assert_eq!(server.requests(), vec!["/movie/603?api_key=token"]);
+}
+
+#[test]
+fn it_searches_movies() {
+    let server = MockServer::start(vec![Route::new("/search/movie", 200, SEARCH)]);
+
+    let movies = client(&server)
+        .search_movies_by_title("The Matrix", Some(1999))
+        .unwrap();
+
+    assert_eq!(movies.len(), 2);
+    assert_eq!(movies[0].tmdb_id, Some(603));
+    assert_eq!(movies[1].title, "The Matrix Revisited");
+    assert_eq!(movies[1].release_year, 2001);
+    assert!(server.requests()[0].contains("primary_release_year=1999"));
+}
+
+#[test]
+fn it_finds_movie_by_external_id() {
+    let server = MockServer::start(vec![Route::new("/find/tt0133093", 200, FIND)]);
+
+    let movie = client(&server)
+        .find_movie(&ExternalId::Imdb("tt0133093".into()))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(movie.tmdb_id, Some(603));
+    assert!(server.requests()[0].contains("external_source=imdb_id"));
+}
+
+#[test]
+fn it_searches_tvshows() {
+    let server = MockServer::start(vec![Route::new("/search/tv", 200, SEARCH_TV)]);
+
+    let shows = client(&server)
+        .search_tvshows_by_title("Game of Thrones", Some(2011))
+        .unwrap();
+
+    assert_eq!(shows.len(), 2);
+    assert_eq!(shows[0].tmdb_id, 1399);
+    assert_eq!(shows[0].first_air_date, Some("2011-04-17".into()));
+    assert_eq!(shows[1].first_air_date, None);
+    assert!(server.requests()[0].contains("first_air_date_year=2011"));
+}
+
+#[test]
+fn it_gets_tvshow_with_seasons() {
+    let server = MockServer::start(vec![
+        Route::new("/tv/1399", 200, TV),
+        Route::new("/tv/1399/season/1", 200, TV_SEASON),
+        Route::new("/tv/1399/external_ids", 200, TV_EXTERNAL_IDS),
+    ]);
+    let client = client(&server);
+
+    let show = client.get_tvshow_detail(1399).unwrap().unwrap();
+    assert_eq!(show.title, "Game of Thrones");
+    assert_eq!(show.seasons.len(), 3);
+    assert_eq!(show.seasons[1].tmdb_id, 3624);
+
+    let season = client.get_tv_season(1399, 1).unwrap().unwrap();
+    assert_eq!(season.number, 1);
+    assert_eq!(season.episodes.len(), 2);
+    assert_eq!(season.episodes[1].title, "The Kingsroad");
+    assert_eq!(season.episodes[1].number, 2);
+    assert_eq!(season.episodes[1].air_date, Some("2011-04-24".into()));
+
+    let ids = client.get_tvshow_external_ids(1399).unwrap();
+    assert_eq!(
+        ids,
+        vec![
+            ExternalId::Imdb("tt0944947".into()),
+            ExternalId::TheTvDb(121361)
+        ]
+    );
+
+    let tvshow = show.into_tvshow(82);
+    assert_eq!(tvshow.tmdb_id, Some(1399));
+    assert_eq!(tvshow.ended, Some("2019-05-19".into()));
+}
+
+#[test]
+fn it_returns_none_for_missing_movie() {
+    let server = MockServer::start(vec![Route::new("/movie/1", 404, ERROR_404)]);
+
+    assert!(client(&server).get_movie_detail(1).unwrap().is_none());
+}
+
+#[test]
+fn it_returns_empty_search_for_missing_resource() {
+    let server = MockServer::start(vec![]);
+
+    assert!(client(&server)
+        .search_movies_by_title("The Matrix", None)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn it_reports_invalid_api_key() {
+    let server = MockServer::start(vec![Route::new("/movie/603", 401, ERROR_401)]);
+
+    let res = client(&server).get_movie_detail(603);
+
+    assert!(matches!(res, Err(Error::Api(ApiError::ApiKey))));
+}
+
+#[test]
+fn it_reports_malformed_body() {
+    let server = MockServer::start(vec![
+        Route::new("/movie/603", 200, &MOVIE[..MOVIE.len() / 2]),
+        Route::new("/search/movie", 200, r#"{"results": "none"}"#),
+    ]);
+    let client = client(&server);
+
+    assert!(matches!(
+        client.get_movie_detail(603),
+        Err(Error::Api(ApiError::InvalidFormat))
+    ));
+    assert!(matches!(
+        client.search_movies_by_title("The Matrix", None),
+        Err(Error::Api(ApiError::InvalidFormat))
+    ));
+}
+
+#[test]
+fn it_retries_transient_failures() {
+    let server = MockServer::start(vec![
+        Route::new("/movie/603", 429, ERROR_500)
+            .header("Retry-After", "0")
+            .times(1),
+        Route::new("/movie/603", 503, ERROR_500).times(1),
+        Route::new("/movie/603", 200, MOVIE),
+    ]);
+
+    let movie = client(&server).get_movie_detail(603).unwrap().unwrap();
+
+    assert_eq!(movie.tmdb_id, Some(603));
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn it_reports_rate_limit() {
+    let server = MockServer::start(vec![
+        Route::new("/movie/603", 429, ERROR_500),
+        Route::new("/movie/604", 429, ERROR_500).header("Retry-After", "3600"),
+    ]);
+    let client = client(&server);
+
+    assert!(matches!(
+        client.get_movie_detail(603),
+        Err(Error::Api(ApiError::RateLimited))
+    ));
+    assert_eq!(server.requests().len(), 3);
+
+    // too long to wait for
+    assert!(matches!(
+        client.get_movie_detail(604),
+        Err(Error::Api(ApiError::RateLimited))
+    ));
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[test]
+fn it_reports_error_message() {
+    let server = MockServer::start(vec![
+        Route::new("/movie/603", 500, ERROR_500),
+        Route::new("/movie/604", 500, "<html>Internal Server Error</html>"),
+    ]);
+    let client = client(&server);
+
+    assert!(matches!(
+        client.get_movie_detail(603),
+        Err(Error::Api(ApiError::Unknown(msg))) if msg.starts_with("Internal error")
+    ));
+    assert!(matches!(
+        client.get_movie_detail(604),
+        Err(Error::Api(ApiError::InvalidFormat))
+    ));
+}
+
+#[test]
+fn it_gets_movie_credits() {
+    let server = MockServer::start(vec![Route::new("/movie/603/credits", 200, MOVIE_CREDITS)]);
+
+    let client = client(&server);
+    let credits = client.get_movie_credits(603).unwrap().unwrap();
+
+    assert_eq!(credits.len(), 6);
+    assert!(credits[0].is_cast());
+    assert_eq!(credits[0].person.name, "Keanu Reeves");
+    assert_eq!(credits[1].character, Some("Morpheus".into()));
+    assert_eq!(credits[2].character, None);
+    assert_eq!(credits[3].role, "Director");
+    assert_eq!(credits[4].role, "Writer");
+    assert_eq!(credits[4].order, None);
+    assert_eq!(server.requests(), vec!["/movie/603/credits?api_key=token"]);
+
+    let provider: &dyn MetadataProvider<IncompleteMovie> = &client;
+    assert_eq!(provider.credits("603").unwrap().unwrap().len(), 6);
+    assert!(provider.credits("tt0133093").unwrap().is_none());
+}
+
+#[test]
+fn it_downloads_movie_artwork() {
+    let image_server = MockServer::start(vec![Route::new(
+        "/t/p/w780/f89U3ADr1oiB1s9GkdPOEpXUk5H.jpg",
+        200,
+        "poster",
+    )]);
+    let configuration = CONFIGURATION.replace("https://image.tmdb.org", &image_server.url());
+    let server = MockServer::start(vec![
+        Route::new("/configuration", 200, configuration),
+        Route::new("/movie/603/images", 200, MOVIE_IMAGES),
+    ]);
+    let client = client(&server);
+
+    let config = client.get_image_configuration().unwrap();
+    let images = client.get_movie_images(603).unwrap().unwrap();
+    assert_eq!(images.posters.len(), 2);
+    assert_eq!(images.logos[0].extension(), "png");
+
+    let poster = images.best(ArtworkKind::Poster, "en").unwrap();
+    let size = config.size(ArtworkKind::Poster, 780);
+    let bytes = client.download_image(&config, poster, size).unwrap();
+    assert_eq!(bytes, b"poster");
+
+    let backdrop = images.best(ArtworkKind::Backdrop, "en").unwrap();
+    assert_eq!(backdrop.language, None);
+    let res = client.download_image(&config, backdrop, "w1280");
+    assert!(matches!(res, Err(Error::Api(ApiError::Unknown(_)))));
+
+    assert_eq!(
+        server.requests(),
+        vec![
+            "/configuration?api_key=token",
+            "/movie/603/images?api_key=token"
+        ]
+    );
+}
+
+#[test]
+fn it_caches_responses() {
+    let server = MockServer::start(vec![
+        Route::new("/movie/603", 200, MOVIE),
+        Route::new("/search/movie", 200, SEARCH),
+    ]);
+    let client = client(&server).with_cache(ResponseCache::open_in_memory().unwrap());
+
+    client.get_movie_detail(603).unwrap();
+    client.get_movie_detail(603).unwrap();
+    client.search_movies_by_title("The Matrix", None).unwrap();
+    client.search_movies_by_title("The Matrix", None).unwrap();
+    assert_eq!(server.requests().len(), 2);
+
+    let movie = client.refresh_movie_detail(603).unwrap().unwrap();
+    assert_eq!(movie.title, "The Matrix");
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn it_serves_only_cached_responses_offline() {
+    let server = MockServer::start(vec![Route::new("/movie/603", 200, MOVIE)]);
+    let path = std::env::temp_dir().join(format!("libmm-cache-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    client(&server)
+        .with_cache(ResponseCache::open(&path).unwrap())
+        .get_movie_detail(603)
+        .unwrap();
+
+    let cache = ResponseCache::open(&path).unwrap().with_offline(true);
+    let offline = TmdbClient::new("token".into())
+        .with_base_url(closed_url())
+        .with_retry_policy(FAST_RETRY)
+        .with_cache(cache);
+
+    let movie = offline.get_movie_detail(603).unwrap().unwrap();
+    assert_eq!(movie.tmdb_id, Some(603));
+    assert!(matches!(
+        offline.get_movie_detail(604),
+        Err(Error::Api(ApiError::Offline))
+    ));
+    assert!(matches!(
+        offline.refresh_movie_detail(603),
+        Err(Error::Api(ApiError::Offline))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn it_reports_transport_error() {
+    let client = TmdbClient::new("token".into())
+        .with_base_url(closed_url())
+        .with_retry_policy(FAST_RETRY);
+
+    let res = client.get_movie_detail(603);
+
+    assert!(matches!(res, Err(Error::Api(ApiError::Transport(_)))));
+}
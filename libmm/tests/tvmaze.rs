@@ -0,0 +1,155 @@
+mod common;
+
+use common::{closed_url, MockServer, Route};
+use libmm::api::{ExternalId, MetadataProvider, RetryPolicy, TvMazeClient};
+use libmm::error::{ApiError, Error};
+use std::time::Duration;
+
+const SEARCH: &str = include_str!("fixtures/tvmaze/search_game_of_thrones.json");
+const SHOW: &str = include_str!("fixtures/tvmaze/show_82.json");
+const SEASONS: &str = include_str!("fixtures/tvmaze/seasons_82.json");
+const EPISODES: &str = include_str!("fixtures/tvmaze/episodes_82.json");
+
+const FAST_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: 2,
+    base_delay: Duration::from_millis(1),
+    max_delay: Duration::from_millis(10),
+};
+
+fn client(server: &MockServer) -> TvMazeClient {
+    TvMazeClient::new()
+        .with_base_url(server.url())
+        .with_retry_policy(FAST_RETRY)
+}
+
+#[test]
+fn it_searches_tvshows() {
+    let server = MockServer::start(vec![Route::new("/search/shows", 200, SEARCH)]);
+    let client = client(&server);
+
+    let shows = client.search_tvshows_by_title("Game of Thrones").unwrap();
+    assert_eq!(shows.len(), 2);
+    assert_eq!(shows[0].tvmaze_id, 82);
+    assert_eq!(shows[0].premiered, Some("2011-04-17".into()));
+
+    let shows = client.search("Game of Thrones", Some(2017)).unwrap();
+    assert_eq!(shows.len(), 1);
+    assert_eq!(shows[0].tvmaze_id, 43687);
+}
+
+#[test]
+fn it_gets_tvshow_detail() {
+    let server = MockServer::start(vec![
+        Route::new("/shows/82", 200, SHOW),
+        Route::new("/lookup/shows", 200, SHOW),
+    ]);
+    let client = client(&server);
+
+    let show = client.get_tvshow_detail(82).unwrap().unwrap();
+    assert_eq!(show.title, "Game of Thrones");
+    assert_eq!(show.ended, Some("2019-05-19".into()));
+
+    let show = client
+        .lookup_tvshow(&ExternalId::Imdb("tt0944947".into()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(show.tvmaze_id, 82);
+    assert_eq!(server.requests()[1], "/lookup/shows?imdb=tt0944947");
+
+    let show = client.detail("82").unwrap().unwrap();
+    assert_eq!(client.id(&show), "82");
+    // ids of other databases are never sent to TVMaze
+    assert!(client.detail("tt0944947").unwrap().is_none());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn it_gets_seasons_and_episodes() {
+    let server = MockServer::start(vec![
+        Route::new("/shows/82/seasons", 200, SEASONS),
+        Route::new("/shows/82/episodes", 200, EPISODES),
+    ]);
+    let client = client(&server);
+
+    let seasons = client.get_seasons(82).unwrap();
+    assert_eq!(seasons.len(), 2);
+    assert_eq!(seasons[1].tvmaze_id, 308);
+    assert_eq!(seasons[1].number, 2);
+
+    let episodes = client.get_episodes(82).unwrap();
+    assert_eq!(episodes.len(), 3);
+    assert_eq!(episodes[2].season_number, 2);
+    assert_eq!(episodes[2].title, "The North Remembers");
+}
+
+#[test]
+fn it_returns_none_for_missing_tvshow() {
+    let server = MockServer::start(vec![]);
+    let client = client(&server);
+
+    assert!(client.get_tvshow_detail(1).unwrap().is_none());
+    assert!(client
+        .lookup_tvshow(&ExternalId::TheTvDb(1))
+        .unwrap()
+        .is_none());
+    assert!(client.get_seasons(1).unwrap().is_empty());
+}
+
+#[test]
+fn it_reports_malformed_body() {
+    let server = MockServer::start(vec![
+        Route::new("/shows/82", 200, &SHOW[..SHOW.len() / 2]),
+        Route::new("/shows/82/episodes", 200, SHOW),
+    ]);
+    let client = client(&server);
+
+    assert!(matches!(
+        client.get_tvshow_detail(82),
+        Err(Error::Api(ApiError::InvalidFormat))
+    ));
+    assert!(matches!(
+        client.get_episodes(82),
+        Err(Error::Api(ApiError::InvalidFormat))
+    ));
+}
+
+#[test]
+fn it_reports_error_status() {
+    let server = MockServer::start(vec![Route::new("/shows/82", 500, "Internal Server Error")]);
+
+    let res = client(&server).get_tvshow_detail(82);
+
+    assert!(matches!(
+        res,
+        Err(Error::Api(ApiError::Unknown(msg))) if msg == "Internal Server Error"
+    ));
+}
+
+#[test]
+fn it_reports_rate_limit() {
+    let server = MockServer::start(vec![
+        Route::new("/shows/82", 429, "")
+            .header("Retry-After", "0")
+            .times(1),
+        Route::new("/shows/82", 200, SHOW),
+        Route::new("/shows/83", 429, ""),
+    ]);
+    let client = client(&server);
+
+    assert!(client.get_tvshow_detail(82).unwrap().is_some());
+    assert!(matches!(
+        client.get_tvshow_detail(83),
+        Err(Error::Api(ApiError::RateLimited))
+    ));
+}
+
+#[test]
+fn it_reports_transport_error() {
+    let client = TvMazeClient::new()
+        .with_base_url(closed_url())
+        .with_retry_policy(FAST_RETRY);
+
+    let res = client.get_tvshow_detail(82);
+
+    assert!(matches!(res, Err(Error::Api(ApiError::Transport(_)))));
+}
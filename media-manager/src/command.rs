@@ -2,23 +2,55 @@ use crate::{AppError, Config};
 use clap::Subcommand;
 use libmm::db::Database;
 
+mod add_episode;
 mod add_movie;
+mod add_tvshow;
+mod edit_movie;
+mod fetch_artwork;
 mod list_movies;
+mod remove_movie;
+mod scan;
+mod show_movie;
+mod show_person;
 
+use add_episode::AddEpisodeCommand;
 use add_movie::AddMovieCommand;
+use add_tvshow::AddTvShowCommand;
+use edit_movie::EditMovieCommand;
+use fetch_artwork::FetchArtworkCommand;
 use list_movies::ListMoviesCommand;
+use remove_movie::RemoveMovieCommand;
+use scan::ScanCommand;
+use show_movie::ShowMovieCommand;
+use show_person::ShowPersonCommand;
 
 #[derive(Debug, Eq, PartialEq, Subcommand)]
 pub enum Command {
     ListMovies(ListMoviesCommand),
+    ShowMovie(ShowMovieCommand),
+    ShowPerson(ShowPersonCommand),
     AddMovie(AddMovieCommand),
+    RemoveMovie(RemoveMovieCommand),
+    EditMovie(EditMovieCommand),
+    FetchArtwork(FetchArtworkCommand),
+    AddTvshow(AddTvShowCommand),
+    AddEpisode(AddEpisodeCommand),
+    Scan(ScanCommand),
 }
 
 impl Command {
     pub fn execute(self, db: &Database, config: &Config) -> Result<(), AppError> {
         match self {
             Self::ListMovies(command) => command.execute(db),
+            Self::ShowMovie(command) => command.execute(db),
+            Self::ShowPerson(command) => command.execute(db),
             Self::AddMovie(command) => command.execute(db, config),
+            Self::RemoveMovie(command) => command.execute(db),
+            Self::EditMovie(command) => command.execute(db, config),
+            Self::FetchArtwork(command) => command.execute(db, config),
+            Self::AddTvshow(command) => command.execute(db, config),
+            Self::AddEpisode(command) => command.execute(db, config),
+            Self::Scan(command) => command.execute(db, config),
         }
     }
 }
@@ -0,0 +1,146 @@
+use crate::input::{get_index, ListIndex};
+use crate::{AppError, Config};
+use clap::Args;
+use libmm::api::TvMazeClient;
+use libmm::db::episode::IncompleteEpisode;
+use libmm::db::season::LoadedSeason;
+use libmm::db::tvshow::LoadedTvShow;
+use libmm::db::{Database, Insertable, Selectable};
+use libmm::media::{EpisodeNumber, NameParser, ParsedEpisode};
+use std::path::{Path, PathBuf};
+
+/// Add episode file of TV show in database
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct AddEpisodeCommand {
+    /// Path to episode file
+    path: PathBuf,
+}
+
+impl AddEpisodeCommand {
+    pub fn execute(&self, db: &Database, config: &Config) -> Result<(), AppError> {
+        let ParsedEpisode { title, number } = episode_from_path(&self.path)?;
+
+        let Some(tvshow) = select_tvshow(db, &title)? else {
+            println!("No episode was added.");
+            return Ok(());
+        };
+
+        let client = config.tvmaze_client()?;
+
+        let episodes = find_episodes(client.get_episodes(tvshow.tvmaze_id)?, &number);
+
+        if episodes.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "No episode {number} of {} was found",
+                tvshow.title
+            )));
+        }
+
+        for episode in episodes {
+            let season = match db.select_season(*tvshow.id(), episode.season_number)? {
+                Some(season) => season,
+                None => insert_season(db, &client, &tvshow, episode.season_number)?,
+            };
+
+            let episode = episode.complete(&season, self.path.clone());
+            let name = format!(
+                "S{:02}E{:02} {}",
+                episode.season_number, episode.number, episode.title
+            );
+            db.insert(episode)?;
+
+            println!("Episode {name} of {} was added to db", tvshow.title);
+        }
+
+        Ok(())
+    }
+}
+
+/// Selects episodes from list of all episodes of TV show
+fn find_episodes(
+    episodes: Vec<IncompleteEpisode>,
+    number: &EpisodeNumber,
+) -> Vec<IncompleteEpisode> {
+    match number {
+        EpisodeNumber::Seasonal {
+            season,
+            episodes: numbers,
+        } => episodes
+            .into_iter()
+            .filter(|e| e.season_number == *season && numbers.contains(&e.number))
+            .collect(),
+        EpisodeNumber::Date { .. } => {
+            let date = number.to_string();
+            episodes
+                .into_iter()
+                .filter(|e| e.airdate.as_ref() == Some(&date))
+                .collect()
+        }
+        EpisodeNumber::Absolute(absolute) => (*absolute as usize)
+            .checked_sub(1)
+            .and_then(|i| episodes.into_iter().nth(i))
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn episode_from_path(path: &Path) -> Result<ParsedEpisode, AppError> {
+    if !path.is_file() {
+        return Err(AppError::invalid_input("Provided path is a not a file"));
+    }
+    let filename = path
+        .file_stem()
+        .ok_or(AppError::invalid_input("Invalid filename"))?;
+
+    NameParser::parse_episode(filename.to_string_lossy()).ok_or(AppError::invalid_input(
+        "Could not find episode number in filename",
+    ))
+}
+
+/// Finds TV show by parsed title, asks user when there is no single match
+fn select_tvshow(db: &Database, title: &str) -> Result<Option<LoadedTvShow>, AppError> {
+    let tvshows: Vec<LoadedTvShow> = db.list_all()?;
+
+    let mut matching = tvshows
+        .iter()
+        .enumerate()
+        .filter(|(_, tvshow)| tvshow.title.eq_ignore_ascii_case(title));
+
+    if let (Some((index, tvshow)), None) = (matching.next(), matching.next()) {
+        println!("Is this the correct TV show? y/n: \"{}\"", tvshow.title);
+
+        if crate::input::ask_confirmation_looped()? {
+            return Ok(tvshows.into_iter().nth(index));
+        }
+    }
+
+    for (i, tvshow) in tvshows.iter().enumerate() {
+        println!("[{}] {}", i + 1, tvshow.title);
+    }
+
+    match get_index("TV show", tvshows.len())? {
+        ListIndex::None => Ok(None),
+        ListIndex::Invalid => Err(AppError::invalid_input("Invalid index given")),
+        ListIndex::Valid(i) => Ok(tvshows.into_iter().nth(i)),
+    }
+}
+
+/// Adds season which was not known when TV show was added
+fn insert_season(
+    db: &Database,
+    client: &TvMazeClient,
+    tvshow: &LoadedTvShow,
+    number: u32,
+) -> Result<LoadedSeason, AppError> {
+    let season = client
+        .get_seasons(tvshow.tvmaze_id)?
+        .into_iter()
+        .find(|s| s.number == number)
+        .ok_or_else(|| {
+            AppError::invalid_input(format!("No season {number} of {} was found", tvshow.title))
+        })?;
+
+    let id = db.insert(season.complete(*tvshow.id()))?;
+
+    Ok(db.select_by_id(id)?.expect("Inserted season was not found"))
+}
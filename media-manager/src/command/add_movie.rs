@@ -1,64 +1,225 @@
+use crate::input::{get_index, InputArgs, ListIndex};
 use crate::{AppError, Config};
 use clap::Args;
-use libmm::api::TmdbClient;
-use libmm::db::movie::CompleteMovie;
+use libmm::api::MetadataProvider;
+use libmm::db::movie::{CompleteMovie, IncompleteMovie};
+use libmm::db::person::IncompleteCredit;
 use libmm::db::{Database, Insertable};
-use libmm::media::{MediaMetadata, NameParser, ParsedName};
+use libmm::error::{ApiError, Error};
+use libmm::media::{MediaMetadata, MovieMatcher, NameParser, ParsedName};
 use std::fs::File;
 use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Add movie to database
 #[derive(Debug, Eq, PartialEq, Args)]
 pub struct AddMovieCommand {
     /// Path to movie file
     path: PathBuf,
+    #[command(flatten)]
+    movie: MovieArgs,
+    #[command(flatten)]
+    input: InputArgs,
+}
+
+/// Values overriding data parsed from filename
+#[derive(Debug, Default, Eq, PartialEq, Args)]
+pub struct MovieArgs {
+    #[arg(long, alias = "tmdb-id", conflicts_with_all = ["title", "year"])]
+    /// Id of movie in database of the movie provider, skips search
+    id: Option<String>,
+    #[arg(long)]
+    /// Title to search for instead of parsed title
+    title: Option<String>,
+    #[arg(long)]
+    /// Release year to search for instead of parsed year
+    year: Option<usize>,
+    #[arg(long)]
+    /// Name of alternate cut
+    cut: Option<String>,
 }
 
 impl AddMovieCommand {
     pub fn execute(&self, db: &Database, config: &Config) -> Result<(), AppError> {
-        let ParsedName { title, year } = name_from_path(self.path.clone())?;
+        let provider = config.movie_provider()?;
+
+        match add_movie(db, provider.as_ref(), &self.path, &self.movie, &self.input)? {
+            AddOutcome::Added(title) => println!("Movie {} was added to db", title),
+            AddOutcome::NotAdded(reason) => println!("{reason}"),
+        }
+
+        Ok(())
+    }
+}
 
-        let title = if ask_if_correct(&title, year) {
-            title
-        } else {
-            crate::input::read_line()?
-        };
+pub enum AddOutcome {
+    /// Title of added movie
+    Added(String),
+    /// Reason why no movie was added
+    NotAdded(&'static str),
+}
 
-        let client = TmdbClient::new(config.tmdb_token.clone());
+/// Identifies movie file and adds it to database with its cast and crew, asks only when allowed by `input`
+pub fn add_movie(
+    db: &Database,
+    provider: &dyn MetadataProvider<IncompleteMovie>,
+    path: &Path,
+    args: &MovieArgs,
+    input: &InputArgs,
+) -> Result<AddOutcome, AppError> {
+    let ParsedName {
+        title,
+        year,
+        edition,
+        release,
+    } = name_from_path(path.to_path_buf())?;
 
-        let results = client.search_movies_by_title(title, year)?;
+    // name is parsed before resolving symlinks, stored path has to be the same however file was given
+    let path = &path
+        .canonicalize()
+        .map_err(|e| AppError::Input(format!("Could not open file {path:?}"), e))?;
+    if db.select_movie_by_path(path)?.is_some() {
+        return Ok(AddOutcome::NotAdded(
+            "Movie file is already in db, no movie was added.",
+        ));
+    }
 
-        for (i, movie) in results.iter().enumerate() {
-            println!("[{}] {} ({})", i + 1, movie.title, movie.release_year);
-        }
+    let id = match &args.id {
+        Some(id) => id.clone(),
+        None => {
+            let overridden = args.title.is_some() || args.year.is_some();
+            let mut parsed = ParsedName {
+                title: args.title.clone().unwrap_or(title),
+                year: args.year.or(year),
+                ..Default::default()
+            };
+            let duration = file_duration(path);
 
-        let index = match get_index(results.len())? {
-            MovieIndex::None => {
-                println!("No movie was added.");
-                return Ok(());
-            }
-            MovieIndex::Invalid => {
-                println!("Invalid index given, no movie was added.");
-                return Ok(());
+            let mut matcher = MovieMatcher::new(&parsed).with_duration(duration);
+            let mut results = provider.search(&parsed.title, parsed.year)?;
+
+            if let Some(movie) = find_confident_match(provider, &matcher, &mut results)? {
+                println!("Found movie {} ({})", movie.title, movie.release_year);
+                provider.id(movie)
+            } else {
+                if !overridden && !input.yes && !ask_if_correct(&parsed.title, parsed.year, input)?
+                {
+                    println!("Enter the correct title:");
+                    parsed.title = crate::input::read_line()?;
+
+                    matcher = MovieMatcher::new(&parsed).with_duration(duration);
+                    results = provider.search(&parsed.title, parsed.year)?;
+                }
+
+                if results.is_empty() {
+                    return Ok(AddOutcome::NotAdded(
+                        "No movie was found, no movie was added.",
+                    ));
+                }
+
+                let ranked = matcher.rank(&results);
+
+                for (i, (index, _)) in ranked.iter().enumerate() {
+                    let movie = &results[*index];
+                    println!("[{}] {} ({})", i + 1, movie.title, movie.release_year);
+                }
+
+                if input.yes {
+                    provider.id(&results[ranked[0].0])
+                } else {
+                    input.require_prompt("movie selection")?;
+
+                    match get_index("movie", results.len())? {
+                        ListIndex::None => return Ok(AddOutcome::NotAdded("No movie was added.")),
+                        ListIndex::Invalid => {
+                            return Ok(AddOutcome::NotAdded(
+                                "Invalid index given, no movie was added.",
+                            ))
+                        }
+                        ListIndex::Valid(i) => provider.id(&results[ranked[i].0]),
+                    }
+                }
             }
-            MovieIndex::Valid(i) => i,
-        };
+        }
+    };
 
-        let detail = client
-            .get_movie_detail(results[index].tmdb_id)?
-            .ok_or(AppError::invalid_input("No movie was found"))?;
+    let detail = provider
+        .detail(&id)?
+        .ok_or(AppError::invalid_input("No movie was found"))?;
 
-        let mut movie = detail.complete(self.path.clone());
+    let mut movie = detail.complete(path.to_path_buf());
+    if !release.is_empty() {
+        *movie.release_mut() = Some(release);
+    }
 
-        handle_alternate_cut(&mut movie, &self.path)?;
+    match &args.cut {
+        Some(cut) => *movie.cut_mut() = Some(cut.clone()),
+        None => {
+            *movie.cut_mut() = edition;
+            handle_alternate_cut(&mut movie, path, input)?;
+        }
+    }
 
-        let title = movie.title.clone();
-        db.insert(movie)?;
-        println!("Movie {} was added to db", title);
+    let credits = fetch_credits(provider, &id)?;
 
-        Ok(())
+    let title = movie.title.clone();
+    let id = db.insert(movie)?;
+    if let Some(credits) = credits {
+        db.set_movie_credits(id, &credits)?;
     }
+
+    Ok(AddOutcome::Added(title))
+}
+
+/// Returns `None` when cast and crew are not cached in offline mode, they are skipped then
+pub fn fetch_credits(
+    provider: &dyn MetadataProvider<IncompleteMovie>,
+    id: &str,
+) -> Result<Option<Vec<IncompleteCredit>>, AppError> {
+    match provider.credits(id) {
+        Ok(credits) => Ok(Some(credits.unwrap_or_default())),
+        Err(Error::Api(ApiError::Offline)) => {
+            println!("Cast and crew are not cached, they were skipped in offline mode");
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Number of best candidates, which get their runtime fetched when match is not clear
+const RUNTIME_CANDIDATES: usize = 3;
+
+/// Returns best search result, when it is clearly the right one.
+///
+/// Runtime of best candidates is fetched only when title and year are not enough.
+fn find_confident_match<'a>(
+    provider: &dyn MetadataProvider<IncompleteMovie>,
+    matcher: &MovieMatcher,
+    results: &'a mut [IncompleteMovie],
+) -> Result<Option<&'a IncompleteMovie>, AppError> {
+    if let Some(index) = matcher.confident_match(results) {
+        return Ok(Some(&results[index]));
+    }
+
+    if results.is_empty() || !matcher.uses_runtime() {
+        return Ok(None);
+    }
+
+    for (index, _) in matcher.rank(results).into_iter().take(RUNTIME_CANDIDATES) {
+        if let Some(detail) = provider.detail(&provider.id(&results[index]))? {
+            results[index] = detail;
+        }
+    }
+
+    Ok(matcher.confident_match(results).map(|i| &results[i]))
+}
+
+/// Duration is only a hint for matching, so unreadable files are not an error here
+fn file_duration(path: &Path) -> Option<Duration> {
+    let file = File::open(path).ok()?;
+
+    MediaMetadata::from_file(file).ok().map(|m| m.duration)
 }
 
 fn name_from_path(path: PathBuf) -> Result<ParsedName, AppError> {
@@ -76,7 +237,9 @@ fn name_from_path(path: PathBuf) -> Result<ParsedName, AppError> {
     Ok(NameParser::parse(filename.to_string_lossy()))
 }
 
-fn ask_if_correct(title: &str, year: Option<usize>) -> bool {
+fn ask_if_correct(title: &str, year: Option<usize>, input: &InputArgs) -> Result<bool, AppError> {
+    input.require_prompt("confirmation of movie title")?;
+
     match year {
         Some(year) => {
             println!("Is this name correct? y/n: \"{title}\", release year: {year}");
@@ -90,43 +253,54 @@ fn ask_if_correct(title: &str, year: Option<usize>) -> bool {
         let confirmation = crate::input::ask_confirmation();
 
         match confirmation {
-            Ok(c) => return c,
-            Err(e) => {
-                println!("{e}");
+            Ok(c) => return Ok(c),
+            Err(AppError::Input(msg, e)) if e.kind() == ErrorKind::InvalidInput => {
+                println!("{}", AppError::Input(msg, e));
                 continue;
             }
+            Err(e) => return Err(e),
         }
     }
 }
 
-enum MovieIndex {
-    None,
-    Valid(usize),
-    Invalid,
-}
+fn handle_alternate_cut(
+    movie: &mut CompleteMovie,
+    path: &Path,
+    input: &InputArgs,
+) -> Result<(), AppError> {
+    if let Some(cut) = movie.cut() {
+        if input.yes {
+            return Ok(());
+        }
+        input.require_prompt("confirmation of alternate cut")?;
 
-fn get_index(max: usize) -> Result<MovieIndex, AppError> {
-    println!("Please select movie by its index, or 0 if none is correct");
+        println!("Found alternate cut \"{cut}\" in filename. Is that correct y/n?");
+        if !crate::input::ask_confirmation_looped()? {
+            println!(
+                "Enter the name of alternate cut, or leave empty if this is not an alternate cut:"
+            );
+            let cut_name = crate::input::read_line()?;
 
-    let index = crate::input::read_line()?
-        .parse()
-        .map_err(|_| AppError::invalid_input("Input is not a number"))?;
+            *movie.cut_mut() = Some(cut_name).filter(|c| !c.is_empty());
+        }
 
-    Ok(match index {
-        0 => MovieIndex::None,
-        i if i <= max => MovieIndex::Valid(i - 1),
-        _ => MovieIndex::Invalid,
-    })
-}
+        return Ok(());
+    }
+
+    // runtime only hints at alternate cut, so unreadable files and unknown runtime skip the check
+    let (Some(duration), Some(runtime)) = (file_duration(path), movie.original_runtime()) else {
+        return Ok(());
+    };
+    let minutes = duration.as_secs() / 60;
 
-fn handle_alternate_cut(movie: &mut CompleteMovie, path: &PathBuf) -> Result<(), AppError> {
-    let file = File::open(&path)
-        .map_err(|e| AppError::Input("Could not open file for metadata info".into(), e))?;
-    let metadata = MediaMetadata::from_file(file)?;
-    let minutes = metadata.duration.as_secs() / 60;
+    if minutes != u64::from(runtime) {
+        if input.yes {
+            println!("File runtime ({} min) is different from TMDB runtime ({} min), use `edit-movie --cut` if this is an alternate cut.", minutes, runtime);
+            return Ok(());
+        }
+        input.require_prompt("name of alternate cut")?;
 
-    if minutes != *movie.original_runtime() as u64 {
-        println!("File runtime ({} min) is different from TMDB runtime ({} min). It's possible that you have special cut of the movie. Is that correct y/n?", minutes, movie.original_runtime());
+        println!("File runtime ({} min) is different from TMDB runtime ({} min). It's possible that you have special cut of the movie. Is that correct y/n?", minutes, runtime);
         if crate::input::ask_confirmation_looped()? {
             println!("Enter the name of alternate cut:");
             let cut_name = crate::input::read_line()?;
@@ -0,0 +1,119 @@
+use crate::input::{get_index, ListIndex};
+use crate::{AppError, Config};
+use clap::Args;
+use libmm::api::{MetadataProvider, TmdbTvShow};
+use libmm::db::tvshow::IncompleteTvShow;
+use libmm::db::{Database, Insertable};
+
+/// Add TV show to database
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct AddTvShowCommand {
+    /// Title of TV show to search for
+    title: String,
+    #[arg(long)]
+    /// Search TMDB instead of TVMaze, episodes are still tracked by TVMaze
+    tmdb: bool,
+}
+
+impl AddTvShowCommand {
+    pub fn execute(&self, db: &Database, config: &Config) -> Result<(), AppError> {
+        let client = config.tvmaze_client()?;
+
+        let tvshow = if self.tmdb {
+            self.select_tmdb_tvshow(&client, config)?
+        } else {
+            self.select_tvmaze_tvshow(&client)?
+        };
+
+        let Some(tvshow) = tvshow else {
+            return Ok(());
+        };
+
+        let seasons = client.get_seasons(tvshow.tvmaze_id)?;
+
+        let title = tvshow.title.clone();
+        let tvshow_id = db.insert(tvshow.complete())?;
+
+        for season in seasons {
+            db.insert(season.complete(tvshow_id))?;
+        }
+
+        println!("TV show {} was added to db", title);
+
+        Ok(())
+    }
+
+    fn select_tvmaze_tvshow(
+        &self,
+        provider: &dyn MetadataProvider<IncompleteTvShow>,
+    ) -> Result<Option<IncompleteTvShow>, AppError> {
+        let results = provider.search(&self.title, None)?;
+
+        for (i, tvshow) in results.iter().enumerate() {
+            match &tvshow.premiered {
+                Some(premiered) => println!("[{}] {} ({})", i + 1, tvshow.title, premiered),
+                None => println!("[{}] {}", i + 1, tvshow.title),
+            }
+        }
+
+        Ok(select_index(results.len())?.and_then(|i| results.into_iter().nth(i)))
+    }
+
+    /// Finds the show selected in TMDB results in TVMaze by ids in other databases
+    fn select_tmdb_tvshow(
+        &self,
+        provider: &dyn MetadataProvider<IncompleteTvShow>,
+        config: &Config,
+    ) -> Result<Option<IncompleteTvShow>, AppError> {
+        let tmdb = config.tmdb_client()?;
+        let results: Vec<TmdbTvShow> = tmdb.search(&self.title, None)?;
+
+        for (i, tvshow) in results.iter().enumerate() {
+            match &tvshow.first_air_date {
+                Some(aired) => println!("[{}] {} ({})", i + 1, tvshow.title, aired),
+                None => println!("[{}] {}", i + 1, tvshow.title),
+            }
+        }
+
+        let Some(selected) = select_index(results.len())?.and_then(|i| results.into_iter().nth(i))
+        else {
+            return Ok(None);
+        };
+
+        let detail = tmdb.detail(&tmdb.id(&selected))?.unwrap_or(selected);
+
+        for external_id in tmdb.get_tvshow_external_ids(detail.tmdb_id)? {
+            if let Some(tvmaze) = provider.find_by_external_id(&external_id)? {
+                return Ok(Some(merge(detail, tvmaze)));
+            }
+        }
+
+        Err(AppError::invalid_input(format!(
+            "TV show {} was not found in TVMaze, which is needed for its episodes",
+            detail.title
+        )))
+    }
+}
+
+/// Keeps TMDB title and dates, which are often better for non-US shows
+fn merge(detail: TmdbTvShow, tvmaze: IncompleteTvShow) -> IncompleteTvShow {
+    let mut tvshow = detail.into_tvshow(tvmaze.tvmaze_id);
+    tvshow.premiered = tvshow.premiered.or(tvmaze.premiered);
+    tvshow.ended = tvshow.ended.or(tvmaze.ended);
+
+    tvshow
+}
+
+fn select_index(len: usize) -> Result<Option<usize>, AppError> {
+    match get_index("TV show", len)? {
+        ListIndex::None => {
+            println!("No TV show was added.");
+            Ok(None)
+        }
+        ListIndex::Invalid => {
+            println!("Invalid index given, no TV show was added.");
+            Ok(None)
+        }
+        ListIndex::Valid(i) => Ok(Some(i)),
+    }
+}
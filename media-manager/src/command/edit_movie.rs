@@ -0,0 +1,102 @@
+use clap::Args;
+use libmm::db::movie::LoadedMovie;
+use libmm::db::{Database, Selectable, Updatable};
+use std::path::PathBuf;
+
+use super::add_movie::fetch_credits;
+use crate::{AppError, Config};
+
+/// Edit movie in database
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct EditMovieCommand {
+    /// Id of movie, as printed by `list-movies`
+    id: usize,
+    #[arg(long, conflicts_with = "no_cut")]
+    /// Name of alternate cut
+    cut: Option<String>,
+    #[arg(long)]
+    /// Remove alternate cut
+    no_cut: bool,
+    #[arg(long)]
+    /// New path to movie file
+    path: Option<PathBuf>,
+    #[arg(long)]
+    /// Title of movie
+    title: Option<String>,
+    #[arg(long, alias = "tmdb-id")]
+    /// Id of movie at configured provider, movie details are fetched again
+    provider_id: Option<String>,
+    #[arg(long)]
+    /// Fetch movie details with cast and crew again, ignoring cached responses
+    refresh: bool,
+}
+
+impl EditMovieCommand {
+    pub fn execute(self, db: &Database, config: &Config) -> Result<(), AppError> {
+        let mut movie: LoadedMovie = db
+            .select_by_id(self.id)?
+            .ok_or_else(|| AppError::invalid_input(format!("No movie with id {}", self.id)))?;
+
+        let lookup = match self.provider_id {
+            Some(id) => Some((config.movie_provider()?, id)),
+            None if self.refresh => Some((
+                config.movie_provider_by_name(&movie.provider)?,
+                movie.provider_id.clone(),
+            )),
+            None => None,
+        };
+
+        if let Some((provider, id)) = lookup {
+            let detail = if self.refresh {
+                provider.refresh(&id)?
+            } else {
+                provider.detail(&id)?
+            };
+
+            let detail = detail.ok_or(AppError::invalid_input("No movie was found"))?;
+            let credits = fetch_credits(provider.as_ref(), &id)?;
+
+            movie.apply_detail(detail);
+            if let Some(credits) = credits {
+                db.set_movie_credits(*movie.id(), &credits)?;
+            }
+        }
+
+        if let Some(title) = self.title {
+            movie.title = title;
+        }
+
+        if let Some(path) = self.path {
+            if !path.is_file() {
+                return Err(AppError::invalid_input("Provided path is a not a file"));
+            }
+
+            // paths are stored canonical, so that the same file is never added twice
+            let path = path
+                .canonicalize()
+                .map_err(|e| AppError::Input(format!("Could not open file {path:?}"), e))?;
+            if let Some(other) = db.select_movie_by_path(&path)? {
+                if other.id() != movie.id() {
+                    return Err(AppError::invalid_input(format!(
+                        "File is already used by movie {} with id {}",
+                        other.title,
+                        other.id()
+                    )));
+                }
+            }
+
+            *movie.path_mut() = path;
+        }
+
+        if self.no_cut {
+            *movie.cut_mut() = None;
+        } else if let Some(cut) = self.cut {
+            *movie.cut_mut() = Some(cut);
+        }
+
+        db.update(&movie)?;
+        println!("Movie {} was updated", movie.title);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use clap::Args;
+use libmm::api::{ImageConfiguration, TmdbClient};
+use libmm::db::artwork::{Artwork, ArtworkKind};
+use libmm::db::movie::LoadedMovie;
+use libmm::db::{Database, Selectable};
+use std::path::PathBuf;
+
+use crate::{AppError, Config};
+
+/// Download posters, backdrops and logos of movies from TMDB
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct FetchArtworkCommand {
+    /// Id of movie, artwork of all movies is backfilled when missing
+    id: Option<usize>,
+    #[arg(long)]
+    /// Save artwork next to movie file as `poster.jpg`, `fanart.jpg` and `clearlogo.png`
+    next_to_file: bool,
+    #[arg(long, requires = "next_to_file")]
+    /// Prefix artwork next to file by its name, for folders holding more movies
+    per_file: bool,
+    #[arg(long)]
+    /// Download artwork again, even when it is already stored
+    force: bool,
+    #[arg(long)]
+    /// Preferred language of text in artwork, original language of movie when missing
+    language: Option<String>,
+}
+
+impl FetchArtworkCommand {
+    pub fn execute(self, db: &Database, config: &Config) -> Result<(), AppError> {
+        let movies: Vec<LoadedMovie> = match self.id {
+            Some(id) => vec![db
+                .select_by_id(id)?
+                .ok_or_else(|| AppError::invalid_input(format!("No movie with id {id}")))?],
+            None => db.list_all()?,
+        };
+
+        let client = config.tmdb_client()?;
+        // fetched only when something is downloaded
+        let mut image_config = None;
+        let mut failed = 0;
+
+        for movie in movies {
+            let stored = db.select_movie_artwork(*movie.id())?;
+            let missing = ArtworkKind::ALL
+                .into_iter()
+                .filter(|kind| self.force || !stored.iter().any(|a| a.kind == *kind))
+                .collect::<Vec<_>>();
+
+            if missing.is_empty() {
+                continue;
+            }
+
+            if image_config.is_none() {
+                image_config = Some(client.get_image_configuration()?);
+            }
+            let image_config = image_config.as_ref().unwrap();
+
+            let res = self.fetch(db, config, &client, image_config, &movie, &missing);
+
+            match res {
+                Ok(0) => println!("{}: no artwork found", movie.title),
+                Ok(count) => println!("{}: {count} artwork downloaded", movie.title),
+                Err(e) => {
+                    println!("{}: {e}", movie.title);
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            println!("\nArtwork of {failed} movies could not be downloaded");
+        }
+
+        Ok(())
+    }
+
+    /// Downloads artwork of `kinds` and stores it, returns how many images were downloaded
+    fn fetch(
+        &self,
+        db: &Database,
+        config: &Config,
+        client: &TmdbClient,
+        image_config: &ImageConfiguration,
+        movie: &LoadedMovie,
+        kinds: &[ArtworkKind],
+    ) -> Result<usize, AppError> {
+        let Some(tmdb_id) = movie.tmdb_id else {
+            return Err(AppError::invalid_input(
+                "Artwork is only available from TMDB",
+            ));
+        };
+        let (dir, prefix) = self.location(config, movie)?;
+        let images = client.get_movie_images(tmdb_id)?.unwrap_or_default();
+        let language = self
+            .language
+            .as_deref()
+            .or(movie.original_language.as_deref())
+            .unwrap_or("en");
+
+        let mut count = 0;
+        for kind in kinds {
+            let Some(image) = images.best(*kind, language) else {
+                continue;
+            };
+
+            let size = image_config.size(*kind, max_width(*kind));
+            let bytes = client.download_image(image_config, image, size)?;
+
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| AppError::Input(format!("Could not create folder {dir:?}"), e))?;
+            let path = dir.join(file_name(&prefix, *kind, image.extension()));
+            std::fs::write(&path, bytes)
+                .map_err(|e| AppError::Input(format!("Could not write artwork to {path:?}"), e))?;
+
+            db.set_movie_artwork(*movie.id(), &Artwork { kind: *kind, path })?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Directory where artwork of movie is saved and prefix of artwork file names
+    fn location(
+        &self,
+        config: &Config,
+        movie: &LoadedMovie,
+    ) -> Result<(PathBuf, String), AppError> {
+        if self.next_to_file {
+            let path = movie.path();
+            let (Some(dir), Some(stem)) = (path.parent(), path.file_stem()) else {
+                return Err(AppError::invalid_input("Movie file has no parent folder"));
+            };
+            let prefix = if self.per_file {
+                format!("{}-", stem.to_string_lossy())
+            } else {
+                String::new()
+            };
+
+            return Ok((dir.to_path_buf(), prefix));
+        }
+
+        config
+            .artwork_dir()
+            .map(|dir| (dir.join(movie.id().to_string()), String::new()))
+            .ok_or_else(|| AppError::Config("Artwork folder is not set".into()))
+    }
+}
+
+/// Names used by media centers like Kodi or Jellyfin
+fn file_name(prefix: &str, kind: ArtworkKind, extension: &str) -> String {
+    match kind {
+        ArtworkKind::Poster => format!("{prefix}poster.{extension}"),
+        ArtworkKind::Backdrop => format!("{prefix}fanart.{extension}"),
+        ArtworkKind::Logo => format!("{prefix}clearlogo.{extension}"),
+    }
+}
+
+/// Largest width downloaded, originals are often unnecessarily large
+fn max_width(kind: ArtworkKind) -> u32 {
+    match kind {
+        ArtworkKind::Poster => 780,
+        ArtworkKind::Backdrop => 1920,
+        ArtworkKind::Logo => 500,
+    }
+}
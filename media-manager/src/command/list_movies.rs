@@ -3,6 +3,7 @@ use libmm::db::movie::LoadedMovie;
 use libmm::db::{Database, Selectable};
 use libmm::media::MediaMetadata;
 use std::fs::File;
+use std::path::Path;
 
 use crate::AppError;
 
@@ -12,15 +13,43 @@ pub struct ListMoviesCommand {
     #[arg(long)]
     /// Print additional info about file
     pub with_metadata: bool,
+    #[arg(long)]
+    /// List only movies with cast or crew member whose name contains given text
+    pub person: Option<String>,
+    #[arg(long, value_name = "LANGUAGE")]
+    /// List only movies without subtitles in given language, like `eng` or `en`
+    pub missing_subtitles: Option<String>,
 }
 
 impl ListMoviesCommand {
     pub fn execute(self, db: &Database) -> Result<(), AppError> {
-        let movies: Vec<LoadedMovie> = db.list_all()?;
+        let movies: Vec<LoadedMovie> = match &self.person {
+            Some(name) => db.select_movies_by_person_name(name)?,
+            None => db.list_all()?,
+        };
 
         for movie in movies {
             let id = movie.id();
             let path = movie.path();
+
+            let metadata = if self.with_metadata || self.missing_subtitles.is_some() {
+                match read_metadata(path) {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        println!("[{id}] {}: {e}", movie.title);
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+
+            if let (Some(language), Some(metadata)) = (&self.missing_subtitles, &metadata) {
+                if metadata.has_subtitles(language) {
+                    continue;
+                }
+            }
+
             let cut = match movie.cut() {
                 Some(cut) => format!("({cut}) "),
                 None => String::new(),
@@ -28,28 +57,41 @@ impl ListMoviesCommand {
             let LoadedMovie {
                 ref title,
                 ref release_year,
-                ref tmdb_id,
+                ref provider,
+                ref provider_id,
                 ..
             } = movie;
 
-            println!("[{id}/tmdb:{tmdb_id}] {title} {cut}({release_year})");
-
-            if self.with_metadata {
-                let file = File::open(path).map_err(|e| {
-                    AppError::Input(
-                        format!(
-                            "Failed to opne file '{}' for metadata reading",
-                            path.to_string_lossy()
-                        ),
-                        e,
+            match movie.release() {
+                Some(release) => {
+                    println!(
+                        "[{id}/{provider}:{provider_id}] {title} {cut}({release_year}) [{release}]"
                     )
-                })?;
-                let metadata = MediaMetadata::from_file(file)?;
+                }
+                None => println!("[{id}/{provider}:{provider_id}] {title} {cut}({release_year})"),
+            }
 
-                println!("    {:?}", metadata);
+            if let Some(metadata) = metadata.filter(|_| self.with_metadata) {
+                for line in metadata.to_string().lines() {
+                    println!("    {line}");
+                }
             }
         }
 
         Ok(())
     }
 }
+
+fn read_metadata(path: &Path) -> Result<MediaMetadata, AppError> {
+    let file = File::open(path).map_err(|e| {
+        AppError::Input(
+            format!(
+                "Failed to open file '{}' for metadata reading",
+                path.to_string_lossy()
+            ),
+            e,
+        )
+    })?;
+
+    Ok(MediaMetadata::from_file(file)?)
+}
@@ -0,0 +1,36 @@
+use clap::Args;
+use libmm::db::movie::LoadedMovie;
+use libmm::db::{Database, Deletable, Selectable};
+
+use crate::AppError;
+
+/// Remove movie from database
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct RemoveMovieCommand {
+    /// Id of movie, as printed by `list-movies`
+    id: usize,
+}
+
+impl RemoveMovieCommand {
+    pub fn execute(self, db: &Database) -> Result<(), AppError> {
+        let movie: LoadedMovie = db
+            .select_by_id(self.id)?
+            .ok_or_else(|| AppError::invalid_input(format!("No movie with id {}", self.id)))?;
+
+        println!(
+            "Do you really want to remove movie {} ({})? y/n",
+            movie.title, movie.release_year
+        );
+
+        if !crate::input::ask_confirmation_looped()? {
+            println!("No movie was removed.");
+            return Ok(());
+        }
+
+        let title = movie.title.clone();
+        db.delete(movie)?;
+        println!("Movie {title} was removed from db");
+
+        Ok(())
+    }
+}
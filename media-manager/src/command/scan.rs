@@ -0,0 +1,125 @@
+use super::add_movie::{add_movie, AddOutcome, MovieArgs};
+use crate::input::InputArgs;
+use crate::{AppError, Config};
+use clap::Args;
+use libmm::db::Database;
+use std::path::{Path, PathBuf};
+
+/// Extensions of containers, which can be probed for metadata
+const VIDEO_EXTENSIONS: &[&str] = &["mkv", "mp4", "m4v", "mov", "avi", "webm", "ts", "m2ts"];
+
+/// Scan directory recursively and add all new movies to database
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct ScanCommand {
+    /// Path to library directory
+    dir: PathBuf,
+    #[command(flatten)]
+    input: InputArgs,
+}
+
+#[derive(Default)]
+struct ScanSummary {
+    added: usize,
+    existing: usize,
+    not_added: usize,
+    failed: Vec<(PathBuf, AppError)>,
+}
+
+impl ScanCommand {
+    pub fn execute(&self, db: &Database, config: &Config) -> Result<(), AppError> {
+        let dir = self
+            .dir
+            .canonicalize()
+            .map_err(|e| AppError::Input(format!("Could not open directory {:?}", self.dir), e))?;
+
+        let mut summary = ScanSummary::default();
+        let mut files = Vec::new();
+        find_video_files(&dir, &mut files, &mut summary.failed);
+        files.sort();
+
+        let provider = config.movie_provider()?;
+        let movie_args = MovieArgs::default();
+
+        for path in files {
+            if db.select_movie_by_path(&path)?.is_some() {
+                summary.existing += 1;
+                continue;
+            }
+
+            println!("\n{}", path.to_string_lossy());
+
+            match add_movie(db, provider.as_ref(), &path, &movie_args, &self.input) {
+                Ok(AddOutcome::Added(title)) => {
+                    println!("Movie {title} was added to db");
+                    summary.added += 1;
+                }
+                Ok(AddOutcome::NotAdded(reason)) => {
+                    println!("{reason}");
+                    summary.not_added += 1;
+                }
+                Err(e) => {
+                    println!("{e}");
+                    summary.failed.push((path, e));
+                }
+            }
+        }
+
+        summary.print();
+
+        Ok(())
+    }
+}
+
+impl ScanSummary {
+    fn print(&self) {
+        println!("\nImport summary:");
+        println!("    added: {}", self.added);
+        println!("    already in db: {}", self.existing);
+        println!("    not added: {}", self.not_added);
+        println!("    failed: {}", self.failed.len());
+
+        for (path, e) in &self.failed {
+            println!("        {}: {e}", path.to_string_lossy());
+        }
+    }
+}
+
+/// Unreadable directories are added to `failed`, the rest of directory tree is still searched
+fn find_video_files(dir: &Path, files: &mut Vec<PathBuf>, failed: &mut Vec<(PathBuf, AppError)>) {
+    let read_error = |e| AppError::Input(format!("Could not read directory {dir:?}"), e);
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return failed.push((dir.to_path_buf(), read_error(e))),
+    };
+
+    for entry in entries {
+        let (entry, file_type) = match entry.and_then(|e| e.file_type().map(|t| (e, t))) {
+            Ok(entry) => entry,
+            Err(e) => {
+                failed.push((dir.to_path_buf(), read_error(e)));
+                continue;
+            }
+        };
+        let path = entry.path();
+
+        if file_type.is_dir() {
+            find_video_files(&path, files, failed);
+        } else if file_type.is_symlink() && path.is_dir() {
+            // symlinked directories are skipped, they can link back to their parent
+            continue;
+        } else if is_video_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn is_video_file(path: &Path) -> bool {
+    match path.extension() {
+        Some(extension) => {
+            let extension = extension.to_string_lossy().to_lowercase();
+            VIDEO_EXTENSIONS.contains(&extension.as_str())
+        }
+        None => false,
+    }
+}
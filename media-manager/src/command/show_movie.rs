@@ -0,0 +1,112 @@
+use clap::Args;
+use libmm::db::movie::LoadedMovie;
+use libmm::db::{Database, Selectable};
+
+use crate::AppError;
+
+/// Number of top billed cast members shown
+const CAST_SHOWN: usize = 10;
+
+/// Show all details of movie
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct ShowMovieCommand {
+    /// Id of movie, as printed by `list-movies`
+    id: usize,
+}
+
+impl ShowMovieCommand {
+    pub fn execute(self, db: &Database) -> Result<(), AppError> {
+        let movie: LoadedMovie = db
+            .select_by_id(self.id)?
+            .ok_or_else(|| AppError::invalid_input(format!("No movie with id {}", self.id)))?;
+
+        println!("{} ({})", movie.title, movie.release_year);
+
+        if let Some(tagline) = &movie.tagline {
+            println!("    {tagline}");
+        }
+        println!();
+
+        if let Some(original_title) = &movie.original_title {
+            match &movie.original_language {
+                Some(language) => println!("Original title: {original_title} [{language}]"),
+                None => println!("Original title: {original_title}"),
+            }
+        }
+        if !movie.genres.is_empty() {
+            let genres = movie
+                .genres
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect::<Vec<_>>();
+            println!("Genres: {}", genres.join(", "));
+        }
+        if let Some(runtime) = movie.original_runtime() {
+            println!("Runtime: {runtime} min");
+        }
+        if let Some(vote_average) = movie.vote_average {
+            println!("Rating: {vote_average:.1}/10");
+        }
+        if let Some(cut) = movie.cut() {
+            println!("Cut: {cut}");
+        }
+        if let Some(release) = movie.release() {
+            println!("Release: {release}");
+        }
+        println!("Path: {}", movie.path().to_string_lossy());
+        for artwork in db.select_movie_artwork(*movie.id())? {
+            println!(
+                "Artwork ({}): {}",
+                artwork.kind,
+                artwork.path.to_string_lossy()
+            );
+        }
+
+        match &movie.imdb_id {
+            Some(imdb_id) => println!(
+                "Ids: {} / {}:{} / imdb:{imdb_id}",
+                movie.id(),
+                movie.provider,
+                movie.provider_id
+            ),
+            None => println!(
+                "Ids: {} / {}:{}",
+                movie.id(),
+                movie.provider,
+                movie.provider_id
+            ),
+        }
+
+        if let Some(overview) = &movie.overview {
+            println!("\n{overview}");
+        }
+
+        let credits = db.select_movie_credits(*movie.id())?;
+        let directors = credits
+            .iter()
+            .filter(|c| c.role == "Director")
+            .map(|c| c.person.name.as_str())
+            .collect::<Vec<_>>();
+        if !directors.is_empty() {
+            println!("\nDirected by {}", directors.join(", "));
+        }
+
+        let cast = credits
+            .iter()
+            .filter(|c| c.is_cast())
+            .take(CAST_SHOWN)
+            .collect::<Vec<_>>();
+        if !cast.is_empty() {
+            println!("\nCast:");
+        }
+        for credit in cast {
+            let id = credit.person.id();
+            match &credit.character {
+                Some(character) => println!("    [{id}] {} as {character}", credit.person.name),
+                None => println!("    [{id}] {}", credit.person.name),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use clap::Args;
+use libmm::db::person::LoadedPerson;
+use libmm::db::{Database, Selectable};
+
+use crate::AppError;
+
+/// Show movies in database with cast or crew member
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct ShowPersonCommand {
+    /// Id of person, or part of their name
+    person: String,
+}
+
+impl ShowPersonCommand {
+    pub fn execute(self, db: &Database) -> Result<(), AppError> {
+        let people: Vec<LoadedPerson> = match self.person.parse::<usize>() {
+            Ok(id) => db.select_by_id(id)?.into_iter().collect(),
+            Err(_) => db.search_people(&self.person)?,
+        };
+
+        if people.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "No person matching {} was found",
+                self.person
+            )));
+        }
+
+        for (i, person) in people.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("[{}/tmdb:{}] {}", person.id(), person.tmdb_id, person.name);
+
+            for movie in db.select_movies_by_person(*person.id())? {
+                let roles = db
+                    .select_movie_credits(*movie.id())?
+                    .into_iter()
+                    .filter(|c| c.person.id() == person.id())
+                    .map(|c| match c.character {
+                        Some(character) => format!("{} as {character}", c.role),
+                        None => c.role,
+                    })
+                    .collect::<Vec<_>>();
+
+                println!(
+                    "    [{}] {} ({}) - {}",
+                    movie.id(),
+                    movie.title,
+                    movie.release_year,
+                    roles.join(", ")
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
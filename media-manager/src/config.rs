@@ -1,13 +1,48 @@
 use crate::AppError;
+use libmm::api::{MetadataProvider, ResponseCache, TmdbClient, TvMazeClient};
+use libmm::db::movie::{IncompleteMovie, TMDB_PROVIDER};
 use std::fmt::Debug;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
-use std::path::Path;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use toml::value::Table;
 use toml::Value;
 
 pub struct Config {
     pub tmdb_token: String,
+    pub movie_provider: MovieProvider,
+    // set from command line, not stored in file
+    cache_path: Option<PathBuf>,
+    offline: bool,
+    artwork_dir: Option<PathBuf>,
+}
+
+/// Source of movie metadata, set by `movie_provider` key
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum MovieProvider {
+    #[default]
+    Tmdb,
+}
+
+impl MovieProvider {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tmdb => TMDB_PROVIDER,
+        }
+    }
+}
+
+impl TryFrom<&str> for MovieProvider {
+    type Error = AppError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            TMDB_PROVIDER => Ok(Self::Tmdb),
+            _ => Err(AppError::Config(
+                format!("Unknown movie provider `{value}`").into(),
+            )),
+        }
+    }
 }
 
 impl Config {
@@ -16,6 +51,7 @@ impl Config {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(&path)
             .map_err(|e| {
                 AppError::Input(format!("Could not open config file at {:?}", &path), e)
@@ -26,26 +62,94 @@ impl Config {
             AppError::Input(format!("Could not read config file at  {:?}", path), e)
         })?;
 
-        let conf = toml::from_str::<Table>(&contents)
+        let mut conf = toml::from_str::<Table>(&contents)
             .map_err(|_| AppError::Config("Invalid configuration format".into()))?;
 
-        if let Ok(config) = Self::try_from(conf) {
-            return Ok(config);
+        if conf.contains_key("tmdb_token") {
+            return Self::try_from(conf);
         }
 
         println!("Please input your TMDB token:");
         let token = crate::input::read_line()?;
+        conf.insert("tmdb_token".into(), Value::String(token));
 
-        let config = Self { tmdb_token: token };
+        let config = Self::try_from(conf)?;
         config.write_to_file(file)?;
 
         Ok(config)
     }
 
+    /// API responses are cached in database at `path`, `offline` disables all requests
+    pub fn with_cache(mut self, path: PathBuf, offline: bool) -> Self {
+        self.cache_path = Some(path);
+        self.offline = offline;
+        self
+    }
+
+    /// Downloaded artwork is stored in `dir`, unless it is stored next to media files
+    pub fn with_artwork_dir(mut self, dir: PathBuf) -> Self {
+        self.artwork_dir = Some(dir);
+        self
+    }
+
+    pub fn artwork_dir(&self) -> Option<&Path> {
+        self.artwork_dir.as_deref()
+    }
+
+    /// Creates client of provider selected in config
+    pub fn movie_provider(&self) -> Result<Box<dyn MetadataProvider<IncompleteMovie>>, AppError> {
+        self.provider_client(self.movie_provider)
+    }
+
+    /// Creates client of provider a stored movie came from, like `tmdb`
+    pub fn movie_provider_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Box<dyn MetadataProvider<IncompleteMovie>>, AppError> {
+        self.provider_client(MovieProvider::try_from(name)?)
+    }
+
+    fn provider_client(
+        &self,
+        provider: MovieProvider,
+    ) -> Result<Box<dyn MetadataProvider<IncompleteMovie>>, AppError> {
+        match provider {
+            MovieProvider::Tmdb => Ok(Box::new(self.tmdb_client()?)),
+        }
+    }
+
+    pub fn tmdb_client(&self) -> Result<TmdbClient, AppError> {
+        let client = TmdbClient::new(self.tmdb_token.clone());
+
+        Ok(match self.response_cache()? {
+            Some(cache) => client.with_cache(cache),
+            None => client,
+        })
+    }
+
+    pub fn tvmaze_client(&self) -> Result<TvMazeClient, AppError> {
+        let client = TvMazeClient::new();
+
+        Ok(match self.response_cache()? {
+            Some(cache) => client.with_cache(cache),
+            None => client,
+        })
+    }
+
+    fn response_cache(&self) -> Result<Option<ResponseCache>, AppError> {
+        match &self.cache_path {
+            Some(path) => Ok(Some(ResponseCache::open(path)?.with_offline(self.offline))),
+            None => Ok(None),
+        }
+    }
+
     fn write_to_file(&self, mut file: File) -> Result<(), AppError> {
         let toml = toml::to_string(&Value::from(self)).expect("Failed to create config");
 
-        file.write_all(toml.as_bytes())
+        // file was already read, so it has to be rewritten from the start
+        file.rewind()
+            .and_then(|_| file.set_len(0))
+            .and_then(|_| file.write_all(toml.as_bytes()))
             .map_err(|_| AppError::Config("Failed to write config file".into()))
     }
 }
@@ -54,25 +158,46 @@ impl TryFrom<Table> for Config {
     type Error = AppError;
 
     fn try_from(table: Table) -> Result<Self, Self::Error> {
-        if let Some(token) = table.get("tmdb_token") {
-            return match token {
-                Value::String(s) => Ok(Self {
-                    tmdb_token: s.clone(),
-                }),
-                _ => Err(AppError::Config("Invalid data type of `tmdb_token`".into())),
-            };
-        } else {
-            Err(AppError::Config("Missing value `tmdb_token`".into()))
-        }
+        let tmdb_token = match table.get("tmdb_token") {
+            Some(Value::String(s)) => s.clone(),
+            Some(_) => return Err(AppError::Config("Invalid data type of `tmdb_token`".into())),
+            None => return Err(AppError::Config("Missing value `tmdb_token`".into())),
+        };
+
+        let movie_provider = match table.get("movie_provider") {
+            Some(Value::String(s)) => MovieProvider::try_from(s.as_str())?,
+            Some(_) => {
+                return Err(AppError::Config(
+                    "Invalid data type of `movie_provider`".into(),
+                ))
+            }
+            None => MovieProvider::default(),
+        };
+
+        Ok(Self {
+            tmdb_token,
+            movie_provider,
+            cache_path: None,
+            offline: false,
+            artwork_dir: None,
+        })
     }
 }
 
 impl From<&Config> for Value {
     fn from(config: &Config) -> Self {
-        let Config { tmdb_token } = config;
+        let Config {
+            tmdb_token,
+            movie_provider,
+            ..
+        } = config;
 
-        let mut config_toml = Table::with_capacity(1);
+        let mut config_toml = Table::with_capacity(2);
         config_toml.insert("tmdb_token".into(), Value::String(tmdb_token.clone()));
+        config_toml.insert(
+            "movie_provider".into(),
+            Value::String(movie_provider.as_str().into()),
+        );
 
         Value::Table(config_toml)
     }
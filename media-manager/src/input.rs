@@ -1,12 +1,57 @@
 use crate::AppError;
-use std::io::{stdin, ErrorKind};
+use clap::Args;
+use std::io::{stdin, ErrorKind, IsTerminal};
+
+/// Controls whether commands can ask for input
+#[derive(Debug, Eq, PartialEq, Args)]
+pub struct InputArgs {
+    #[arg(long, short)]
+    /// Accept parsed names, first search results and detected cuts without asking
+    pub yes: bool,
+    #[arg(long)]
+    /// Fail instead of asking for input
+    pub no_input: bool,
+}
+
+impl InputArgs {
+    /// Input is never read from stdin which is not a terminal, so scripts don't hang
+    pub fn can_prompt(&self) -> bool {
+        !self.no_input && stdin().is_terminal()
+    }
+
+    /// Returns error when user cannot be asked for `what`
+    pub fn require_prompt(&self, what: &str) -> Result<(), AppError> {
+        if self.can_prompt() {
+            Ok(())
+        } else {
+            Err(AppError::Input(
+                format!("Cannot ask for {what}, input is disabled or stdin is not a terminal"),
+                ErrorKind::Unsupported.into(),
+            ))
+        }
+    }
+}
+
+/// Reads line into buffer, end of input is an error
+fn read_stdin(buf: &mut String) -> Result<(), AppError> {
+    let read = stdin()
+        .read_line(buf)
+        .map_err(|e| AppError::Input("Error reading input".into(), e))?;
+
+    if read == 0 {
+        return Err(AppError::Input(
+            "No more input".into(),
+            ErrorKind::UnexpectedEof.into(),
+        ));
+    }
+
+    Ok(())
+}
 
 pub fn ask_confirmation() -> Result<bool, AppError> {
     let mut buf = String::with_capacity(4);
 
-    stdin()
-        .read_line(&mut buf)
-        .map_err(|e| AppError::Input("Error reading input".into(), e))?;
+    read_stdin(&mut buf)?;
 
     match buf.trim() {
         "y" => Ok(true),
@@ -22,9 +67,8 @@ pub fn ask_confirmation_looped() -> Result<bool, AppError> {
     let mut buf = String::with_capacity(4);
 
     loop {
-        stdin()
-            .read_line(&mut buf)
-            .map_err(|e| AppError::Input("Error reading input".into(), e))?;
+        buf.clear();
+        read_stdin(&mut buf)?;
 
         match buf.trim() {
             "y" => return Ok(true),
@@ -39,12 +83,31 @@ pub fn ask_confirmation_looped() -> Result<bool, AppError> {
 
 pub fn read_line() -> Result<String, AppError> {
     let mut buf = String::new();
-    stdin()
-        .read_line(&mut buf)
-        .map_err(|e| AppError::Input("Failed to read input".into(), e))?;
+    read_stdin(&mut buf)?;
 
     // trim right in-place
     buf.truncate(buf.trim_end().len());
 
     Ok(buf)
 }
+
+pub enum ListIndex {
+    None,
+    Valid(usize),
+    Invalid,
+}
+
+/// Asks for 1-based index into list of `max` items
+pub fn get_index(item_name: &str, max: usize) -> Result<ListIndex, AppError> {
+    println!("Please select {item_name} by its index, or 0 if none is correct");
+
+    let index = read_line()?
+        .parse()
+        .map_err(|_| AppError::invalid_input("Input is not a number"))?;
+
+    Ok(match index {
+        0 => ListIndex::None,
+        i if i <= max => ListIndex::Valid(i - 1),
+        _ => ListIndex::Invalid,
+    })
+}
@@ -28,7 +28,9 @@ fn run() -> Result<(), AppError> {
     paths.make_dirs()?;
 
     let db = libmm::db::Database::open(paths.db_path)?;
-    let config = Config::init(paths.config_path)?;
+    let config = Config::init(paths.config_path)?
+        .with_cache(paths.cache_path, args.offline)
+        .with_artwork_dir(paths.artwork_dir);
 
     args.command.execute(&db, &config)?;
 
@@ -39,4 +41,7 @@ fn run() -> Result<(), AppError> {
 struct Args {
     #[command(subcommand)]
     command: Command,
+    /// Use only cached responses of metadata APIs, no requests are sent
+    #[arg(long, global = true)]
+    offline: bool,
 }
@@ -6,10 +6,14 @@ use crate::AppError;
 
 const DEFAULT_DB_NAME: &str = "data.db";
 const DEFAULT_CONFIG_NAME: &str = "config.toml";
+const DEFAULT_CACHE_NAME: &str = "cache.db";
+const DEFAULT_ARTWORK_DIR: &str = "artwork";
 
 pub struct Paths {
     pub db_path: PathBuf,
     pub config_path: PathBuf,
+    pub cache_path: PathBuf,
+    pub artwork_dir: PathBuf,
 }
 
 impl Paths {
@@ -22,13 +26,18 @@ impl Paths {
             Some(dirs) => {
                 let mut db_path = dirs.data_dir().to_owned();
                 let mut config_path = dirs.config_dir().to_owned();
+                let mut cache_path = dirs.cache_dir().to_owned();
+                let artwork_dir = dirs.data_dir().join(DEFAULT_ARTWORK_DIR);
 
                 db_path.push(DEFAULT_DB_NAME);
                 config_path.push(DEFAULT_CONFIG_NAME);
+                cache_path.push(DEFAULT_CACHE_NAME);
 
                 Ok(Self {
                     db_path,
                     config_path,
+                    cache_path,
+                    artwork_dir,
                 })
             }
         }
@@ -44,6 +53,13 @@ impl Paths {
             AppError::Input("Could not create folder for configuration".to_owned(), e)
         })?;
 
+        let cache_folder = self.cache_path.parent().unwrap();
+        std::fs::create_dir_all(cache_folder)
+            .map_err(|e| AppError::Input("Could not create folder for cache".to_owned(), e))?;
+
+        std::fs::create_dir_all(&self.artwork_dir)
+            .map_err(|e| AppError::Input("Could not create folder for artwork".to_owned(), e))?;
+
         Ok(())
     }
 }